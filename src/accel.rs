@@ -0,0 +1,65 @@
+use crate::{Alphabet, StateId};
+
+// States with more ASCII exits than this are stepped through one char at a
+// time.
+const MAX_EXIT_BYTES: usize = 3;
+
+const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+// Skips over the self-loop of a DFA state by scanning for the few ASCII bytes
+// that leave it. Non-ASCII bytes always stop the scan, since the state may or
+// may not loop on the char they start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accelerator {
+    exits: Vec<u8>,
+}
+
+impl Accelerator {
+    pub(crate) fn new(
+        state: StateId,
+        next_state: impl Fn(Alphabet) -> Option<StateId>,
+    ) -> Option<Self> {
+        let mut exits: Vec<u8> = Vec::new();
+        for b in 0..0x80u8 {
+            if next_state(b as char) != Some(state) {
+                if exits.len() == MAX_EXIT_BYTES {
+                    return None;
+                }
+                exits.push(b);
+            }
+        }
+        Some(Self { exits })
+    }
+
+    pub fn exits(&self) -> &[u8] {
+        &self.exits
+    }
+
+    // Returns the offset of the first byte at or after `start` that is not
+    // known to loop back into the state, or the length of the haystack.
+    pub(crate) fn skip(&self, haystack: &[u8], start: usize) -> usize {
+        let mut at = start;
+        while let Some(chunk) = haystack.get(at..at + 8) {
+            let word = u64::from_le_bytes(chunk.try_into().expect("chunk has eight bytes"));
+            let is_exit = word & HIGH_BITS != 0
+                || self
+                    .exits
+                    .iter()
+                    .any(|&b| has_zero_byte(word ^ LOW_BITS.wrapping_mul(b as u64)));
+            if is_exit {
+                break;
+            }
+            at += 8;
+        }
+
+        while at < haystack.len() && haystack[at] < 0x80 && !self.exits.contains(&haystack[at]) {
+            at += 1;
+        }
+        at
+    }
+}
+
+fn has_zero_byte(word: u64) -> bool {
+    word.wrapping_sub(LOW_BITS) & !word & HIGH_BITS != 0
+}
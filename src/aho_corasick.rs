@@ -0,0 +1,286 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::OnceLock,
+};
+
+use crate::{
+    StateId,
+    search::{Anchors, Match, MatchKind, ReverseSearcher, Searcher, Threads, rfind_reversed},
+    stream::StreamMatcher,
+};
+
+pub(crate) const ROOT: StateId = 0;
+
+#[derive(Debug, Clone)]
+struct AhoCorasickState {
+    transitions: HashMap<u8, StateId>,
+    fail: StateId,
+    is_pattern: bool,
+    // Lengths and indexes of every pattern ending in this state, including the
+    // ones only reachable through the failure links.
+    outputs: Vec<(usize, usize)>,
+}
+
+impl AhoCorasickState {
+    fn new() -> Self {
+        Self {
+            transitions: HashMap::new(),
+            fail: ROOT,
+            is_pattern: false,
+            outputs: Vec::new(),
+        }
+    }
+}
+
+// A byte-level Aho–Corasick automaton over a set of literals.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    states: Vec<AhoCorasickState>,
+    // In the order they were given, which leftmost-first goes by.
+    patterns: Vec<String>,
+    max_pattern_len: usize,
+    match_kind: MatchKind,
+    reversed: OnceLock<Box<AhoCorasick>>,
+}
+
+impl AhoCorasick {
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let patterns: Vec<String> = patterns
+            .into_iter()
+            .map(|pattern| pattern.as_ref().to_string())
+            .collect();
+        let mut states = vec![AhoCorasickState::new()];
+        let mut max_pattern_len = 0;
+        for (index, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_bytes();
+            let mut curr = ROOT;
+            for &b in pattern {
+                curr = match states[curr].transitions.get(&b) {
+                    Some(&next) => next,
+                    None => {
+                        let next: StateId = states.len();
+                        states.push(AhoCorasickState::new());
+                        states[curr].transitions.insert(b, next);
+                        next
+                    }
+                };
+            }
+
+            if !states[curr].is_pattern {
+                states[curr].is_pattern = true;
+                states[curr].outputs.push((pattern.len(), index));
+            }
+            max_pattern_len = max_pattern_len.max(pattern.len());
+        }
+
+        let mut queue: VecDeque<StateId> = VecDeque::from([ROOT]);
+        while let Some(curr) = queue.pop_front() {
+            let mut transitions: Vec<(u8, StateId)> = states[curr]
+                .transitions
+                .iter()
+                .map(|(&b, &next)| (b, next))
+                .collect();
+            transitions.sort_unstable();
+
+            for (b, next) in transitions {
+                let fail = if curr == ROOT {
+                    ROOT
+                } else {
+                    Self::follow(&states, states[curr].fail, b)
+                };
+                states[next].fail = fail;
+                let inherited = states[fail].outputs.clone();
+                states[next].outputs.extend(inherited);
+                queue.push_back(next);
+            }
+        }
+
+        Self {
+            states,
+            patterns,
+            max_pattern_len,
+            match_kind: MatchKind::default(),
+            reversed: OnceLock::new(),
+        }
+    }
+
+    // Leftmost-first prefers the pattern given first, as an alternation of the
+    // patterns would.
+    pub fn with_match_kind(mut self, match_kind: MatchKind) -> Self {
+        self.match_kind = match_kind;
+        self
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    // Rebuilds the automaton from the patterns spelled backwards, in the same
+    // order.
+    pub fn reverse(&self) -> AhoCorasick {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| pattern.chars().rev().collect::<String>());
+        AhoCorasick::new(patterns).with_match_kind(self.match_kind)
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        input
+            .chars()
+            .try_fold(ROOT, |curr, c| self.next_state(curr, c))
+            .is_some_and(|curr| self.is_pattern(curr))
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_literal_set(self)
+    }
+
+    // Follows the trie, without failure links, over the bytes of a char.
+    pub(crate) fn next_state(&self, state: StateId, c: char) -> Option<StateId> {
+        let mut buf = [0; 4];
+        c.encode_utf8(&mut buf).bytes().try_fold(state, |curr, b| {
+            self.states[curr].transitions.get(&b).copied()
+        })
+    }
+
+    pub(crate) fn is_pattern(&self, state: StateId) -> bool {
+        self.states[state].is_pattern
+    }
+
+    // The index of the pattern spelled by the path to `state`, which comes
+    // first among the outputs since it is added before the inherited ones.
+    fn pattern_index(&self, state: StateId) -> Option<usize> {
+        let state = &self.states[state];
+        state.is_pattern.then(|| state.outputs[0].1)
+    }
+
+    fn follow(states: &[AhoCorasickState], mut curr: StateId, b: u8) -> StateId {
+        loop {
+            if let Some(&next) = states[curr].transitions.get(&b) {
+                return next;
+            }
+            if curr == ROOT {
+                return ROOT;
+            }
+            curr = states[curr].fail;
+        }
+    }
+}
+
+// A thread follows the trie from where it started, and carries the first of
+// the patterns it has spelled so far. Under leftmost-first a longer pattern
+// only replaces a shorter one starting at the same place if it comes first.
+impl Threads for AhoCorasick {
+    type State = (StateId, usize);
+    type Held = HashSet<StateId>;
+
+    fn start_state(&self) -> (StateId, usize) {
+        (ROOT, self.pattern_index(ROOT).unwrap_or(usize::MAX))
+    }
+
+    fn next_states(
+        &self,
+        (state, first): (StateId, usize),
+        c: char,
+        mut push: impl FnMut((StateId, usize)),
+    ) {
+        if let Some(next) = self.next_state(state, c) {
+            push((
+                next,
+                self.pattern_index(next)
+                    .map_or(first, |index| index.min(first)),
+            ));
+        }
+    }
+
+    fn hold(
+        &self,
+        held: &mut HashSet<StateId>,
+        state: (StateId, usize),
+    ) -> Option<(StateId, usize)> {
+        held.insert(state.0).then_some(state)
+    }
+
+    fn release(&self, held: &mut HashSet<StateId>) {
+        held.clear();
+    }
+
+    fn thread_accepts(&self, (state, first): (StateId, usize)) -> bool {
+        match self.pattern_index(state) {
+            Some(index) => self.match_kind != MatchKind::LeftmostFirst || index == first,
+            None => false,
+        }
+    }
+}
+
+impl Searcher for AhoCorasick {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        let bytes = haystack.as_bytes();
+        // The start, end and pattern index of the best match so far.
+        let mut best: Option<(usize, usize, usize)> = self.states[ROOT]
+            .outputs
+            .first()
+            .map(|&(_, pattern)| (start, start, pattern));
+
+        let mut curr = ROOT;
+        for (i, &b) in bytes.iter().enumerate().skip(start) {
+            // Nothing starting at or before the best match so far can end past
+            // this point, and anything starting after it loses anyway. An
+            // earliest search is done as soon as any match ends.
+            if let Some((best_start, best_end, _)) = best
+                && (i >= best_start + self.max_pattern_len
+                    || self.match_kind == MatchKind::Earliest && i >= best_end)
+            {
+                break;
+            }
+
+            curr = Self::follow(&self.states, curr, b);
+            for &(len, pattern) in &self.states[curr].outputs {
+                let (match_start, match_end) = (i + 1 - len, i + 1);
+                if len == 0 && !haystack.is_char_boundary(match_end) {
+                    continue;
+                }
+                let is_better = match best {
+                    Some((best_start, best_end, best_pattern)) => {
+                        match_start < best_start
+                            || (match_start == best_start
+                                && match self.match_kind {
+                                    MatchKind::LeftmostFirst => pattern < best_pattern,
+                                    MatchKind::LeftmostLongest => match_end > best_end,
+                                    MatchKind::Earliest => false,
+                                })
+                    }
+                    None => true,
+                };
+                if is_better {
+                    best = Some((match_start, match_end, pattern));
+                }
+            }
+        }
+
+        best.map(|(match_start, match_end, _)| Match::new(haystack, match_start, match_end))
+    }
+}
+
+// The patterns are plain literals, so there are no anchors to trade places.
+impl ReverseSearcher for AhoCorasick {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        let reversed = self.reversed.get_or_init(|| Box::new(self.reverse()));
+        rfind_reversed(haystack, end, &Anchors::default(), |haystack, start, _| {
+            reversed.find_at(haystack, start)
+        })
+    }
+}
@@ -0,0 +1,481 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    Alphabet,
+    epsilon_nfa::{EpsilonNfa, EpsilonNfaBuilder, Fragment},
+    glushkov::{Glushkov, GlushkovBuilder, MAX_GLUSHKOV_POSITIONS},
+    prefilter::Prefilter,
+    symbol::Symbol,
+};
+
+pub(crate) const MAX_EPSILON_NFA_STATES: usize = 1 << 20;
+
+// A pattern over chars, or over any other symbols when built by hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ast<A = Alphabet> {
+    Empty,
+    Class(Vec<RangeInclusive<A>>),
+    Concat(Vec<Ast<A>>),
+    Alternation(Vec<Ast<A>>),
+    Repeat {
+        inner: Box<Ast<A>>,
+        min: usize,
+        max: Option<usize>,
+    },
+    // A named group. Matches do not report captures, so it matches whatever
+    // `inner` does.
+    Capture {
+        name: String,
+        inner: Box<Ast<A>>,
+    },
+}
+
+impl<A: Symbol> Ast<A> {
+    pub fn literal(c: A) -> Self {
+        Ast::Class(vec![c..=c])
+    }
+
+    pub fn class<I: IntoIterator<Item = A>>(chars: I) -> Self {
+        let mut chars: Vec<A> = chars.into_iter().collect();
+        chars.sort_unstable();
+        chars.dedup();
+
+        let mut ranges: Vec<RangeInclusive<A>> = Vec::new();
+        for c in chars {
+            match ranges.last_mut() {
+                Some(range) if range.end().successor() == Some(c) => {
+                    *range = *range.start()..=c;
+                }
+                _ => ranges.push(c..=c),
+            }
+        }
+        Ast::Class(ranges)
+    }
+
+    // Like class, but from ranges that may overlap or come in any order.
+    pub fn class_ranges<I: IntoIterator<Item = RangeInclusive<A>>>(ranges: I) -> Self {
+        let mut ranges: Vec<RangeInclusive<A>> = ranges
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect();
+        ranges.sort_unstable_by_key(|range| *range.start());
+
+        let mut merged: Vec<RangeInclusive<A>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last)
+                    if last.end() >= range.start()
+                        || last.end().successor() == Some(*range.start()) =>
+                {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+        Ast::Class(merged)
+    }
+
+    pub fn concat(left: Ast<A>, right: Ast<A>) -> Self {
+        match (left, right) {
+            (Ast::Concat(mut left), Ast::Concat(right)) => {
+                left.extend(right);
+                Ast::Concat(left)
+            }
+            (Ast::Concat(mut left), right) => {
+                left.push(right);
+                Ast::Concat(left)
+            }
+            (left, Ast::Concat(mut right)) => {
+                right.insert(0, left);
+                Ast::Concat(right)
+            }
+            (left, right) => Ast::Concat(vec![left, right]),
+        }
+    }
+
+    pub fn alternation(mut alternatives: Vec<Ast<A>>) -> Self {
+        if alternatives.len() == 1 {
+            return alternatives
+                .pop()
+                .expect("alternatives has exactly one element");
+        }
+        Ast::Alternation(alternatives)
+    }
+
+    pub fn repeat(inner: Ast<A>, min: usize, max: Option<usize>) -> Self {
+        Ast::Repeat {
+            inner: Box::new(inner),
+            min,
+            max,
+        }
+    }
+
+    pub fn capture(inner: Ast<A>, name: &str) -> Self {
+        Ast::Capture {
+            name: name.to_string(),
+            inner: Box::new(inner),
+        }
+    }
+
+    // The pattern matching exactly the reversals of the strings this one
+    // matches.
+    pub fn reverse(&self) -> Ast<A> {
+        match self {
+            Ast::Empty | Ast::Class(_) => self.clone(),
+            Ast::Concat(items) => Ast::Concat(items.iter().rev().map(Ast::reverse).collect()),
+            Ast::Alternation(alternatives) => {
+                Ast::Alternation(alternatives.iter().map(Ast::reverse).collect())
+            }
+            Ast::Repeat { inner, min, max } => Ast::repeat(inner.reverse(), *min, *max),
+            Ast::Capture { name, inner } => Ast::capture(inner.reverse(), name),
+        }
+    }
+
+    pub fn to_epsilon_nfa(&self) -> Result<EpsilonNfa<A>, String> {
+        self.to_epsilon_nfa_with_size_limit(MAX_EPSILON_NFA_STATES)
+    }
+
+    pub fn to_epsilon_nfa_with_size_limit(
+        &self,
+        size_limit: usize,
+    ) -> Result<EpsilonNfa<A>, String> {
+        let estimated_states = self.estimated_states();
+        if estimated_states > size_limit {
+            return Err(format!(
+                "Pattern would compile to {estimated_states} states, exceeding the limit of {size_limit}"
+            ));
+        }
+
+        let mut epsilon_nfa_builder = EpsilonNfaBuilder::default();
+        let fragment = self.compile(&mut epsilon_nfa_builder);
+        Ok(epsilon_nfa_builder.finish(fragment))
+    }
+
+    fn estimated_states(&self) -> usize {
+        match self {
+            Ast::Empty | Ast::Class(_) => 1,
+            Ast::Concat(items) => items.iter().fold(0, |total, item| {
+                total.saturating_add(item.estimated_states())
+            }),
+            Ast::Alternation(alternatives) => alternatives.iter().fold(1, |total, item| {
+                total.saturating_add(item.estimated_states())
+            }),
+            Ast::Repeat { inner, min, max } => match (inner.as_ref(), min, max) {
+                (_, 0 | 1, None) | (_, 0, Some(1)) => inner.estimated_states().saturating_add(1),
+                (Ast::Class(_), _, _) => 1,
+                _ => inner
+                    .estimated_states()
+                    .saturating_add(1)
+                    .saturating_mul(max.unwrap_or(min + 1))
+                    .saturating_add(1),
+            },
+            Ast::Capture { inner, .. } => inner.estimated_states(),
+        }
+    }
+
+    // The number of character classes once every counted repetition has been
+    // written out, which is the state count of the Glushkov automaton.
+    pub fn position_count(&self) -> usize {
+        match self {
+            Ast::Empty => 0,
+            Ast::Class(_) => 1,
+            Ast::Concat(items) | Ast::Alternation(items) => items
+                .iter()
+                .fold(0, |total, item| total.saturating_add(item.position_count())),
+            Ast::Repeat { inner, min, max } => match (min, max) {
+                (0 | 1, None) | (0, Some(1)) => inner.position_count(),
+                _ => inner
+                    .position_count()
+                    .saturating_mul(max.unwrap_or(min + 1)),
+            },
+            Ast::Capture { inner, .. } => inner.position_count(),
+        }
+    }
+
+    fn compile(&self, epsilon_nfa_builder: &mut EpsilonNfaBuilder<A>) -> Fragment {
+        match self {
+            Ast::Empty => epsilon_nfa_builder.add_empty(),
+            Ast::Class(ranges) => epsilon_nfa_builder.add_transition_ranges(ranges.clone()),
+            Ast::Concat(items) => {
+                let mut concat_res: Option<Fragment> = None;
+                for item in items {
+                    let item_res = item.compile(epsilon_nfa_builder);
+                    concat_res = Some(match concat_res {
+                        Some(concat_res) => {
+                            epsilon_nfa_builder.add_concat_transition(concat_res, item_res)
+                        }
+                        None => item_res,
+                    });
+                }
+                concat_res.unwrap_or_else(|| epsilon_nfa_builder.add_empty())
+            }
+            Ast::Alternation(alternatives) => {
+                let alternatives: Vec<Fragment> = alternatives
+                    .iter()
+                    .map(|alternative| alternative.compile(epsilon_nfa_builder))
+                    .collect();
+                epsilon_nfa_builder.add_union_transition(alternatives)
+            }
+            Ast::Repeat { inner, min, max } => match (inner.as_ref(), *min, *max) {
+                (_, 0, None) => {
+                    let inner_res = inner.compile(epsilon_nfa_builder);
+                    epsilon_nfa_builder.add_star_transition(inner_res)
+                }
+                (_, 1, None) => {
+                    let inner_res = inner.compile(epsilon_nfa_builder);
+                    epsilon_nfa_builder.add_plus_transition(inner_res)
+                }
+                (_, 0, Some(1)) => {
+                    let inner_res = inner.compile(epsilon_nfa_builder);
+                    epsilon_nfa_builder.add_question_transition(inner_res)
+                }
+                (Ast::Class(ranges), min, max) => {
+                    epsilon_nfa_builder.add_counted_transition(ranges.clone(), min, max)
+                }
+                (inner, min, max) => inner.compile_counted(epsilon_nfa_builder, min, max),
+            },
+            Ast::Capture { inner, .. } => inner.compile(epsilon_nfa_builder),
+        }
+    }
+
+    fn compile_counted(
+        &self,
+        epsilon_nfa_builder: &mut EpsilonNfaBuilder<A>,
+        min: usize,
+        max: Option<usize>,
+    ) -> Fragment {
+        let mut repeated_res: Option<Fragment> = None;
+        for _ in 0..min {
+            let inner_res = self.compile(epsilon_nfa_builder);
+            repeated_res = Some(match repeated_res {
+                Some(repeated_res) => {
+                    epsilon_nfa_builder.add_concat_transition(repeated_res, inner_res)
+                }
+                None => inner_res,
+            });
+        }
+
+        // The optional copies are nested as (x(x(x)?)?)? rather than chained as
+        // x?x?x? so that every copy shares a single exit instead of each one
+        // being able to skip all of the copies after it.
+        let optional_copies = match max {
+            Some(max) => max - min,
+            None => 1,
+        };
+        let mut optional_res: Option<Fragment> = None;
+        for _ in 0..optional_copies {
+            let inner_res = self.compile(epsilon_nfa_builder);
+            let inner_res = match optional_res {
+                Some(optional_res) => {
+                    epsilon_nfa_builder.add_concat_transition(inner_res, optional_res)
+                }
+                None => inner_res,
+            };
+            optional_res = Some(match max {
+                Some(_) => epsilon_nfa_builder.add_question_transition(inner_res),
+                None => epsilon_nfa_builder.add_star_transition(inner_res),
+            });
+        }
+
+        match (repeated_res, optional_res) {
+            (Some(repeated_res), Some(optional_res)) => {
+                epsilon_nfa_builder.add_concat_transition(repeated_res, optional_res)
+            }
+            (Some(res), None) | (None, Some(res)) => res,
+            (None, None) => epsilon_nfa_builder.add_empty(),
+        }
+    }
+}
+
+impl Ast {
+    pub fn to_glushkov(&self) -> Result<Glushkov, String> {
+        let position_count = self.position_count();
+        if position_count > MAX_GLUSHKOV_POSITIONS {
+            return Err(format!(
+                "Pattern has {position_count} positions, exceeding the limit of {MAX_GLUSHKOV_POSITIONS} for a bit-parallel matcher"
+            ));
+        }
+
+        Ok(GlushkovBuilder::new().build(self, self.prefilter()))
+    }
+
+    pub fn prefilter(&self) -> Option<Prefilter> {
+        Prefilter::new(self)
+    }
+
+    // The number of chars in the largest class, which bounds the transitions an
+    // NFA or DFA state can need.
+    pub(crate) fn max_class_len(&self) -> usize {
+        match self {
+            Ast::Empty => 0,
+            Ast::Class(ranges) => ranges
+                .iter()
+                .map(|range| (*range.end() as usize + 1).saturating_sub(*range.start() as usize))
+                .sum(),
+            Ast::Concat(items) | Ast::Alternation(items) => {
+                items.iter().map(Ast::max_class_len).max().unwrap_or(0)
+            }
+            Ast::Repeat { inner, .. } | Ast::Capture { inner, .. } => inner.max_class_len(),
+        }
+    }
+
+    // The literals making up the whole language when the pattern is nothing but
+    // a literal or an alternation of literals. An alternation of nothing has no
+    // literals to search for.
+    pub(crate) fn literals(&self) -> Option<Vec<String>> {
+        match self {
+            Ast::Alternation(alternatives) if alternatives.is_empty() => None,
+            Ast::Alternation(alternatives) => {
+                let mut literals: Vec<String> = Vec::new();
+                for alternative in alternatives {
+                    literals.extend(alternative.literals()?);
+                }
+                Some(literals)
+            }
+            Ast::Capture { inner, .. } => inner.literals(),
+            _ => match self.literal_prefix() {
+                (literal, true) => Some(vec![literal]),
+                _ => None,
+            },
+        }
+    }
+
+    // The literal every match starts with, and whether the pattern matches
+    // nothing but that literal.
+    pub(crate) fn literal_prefix(&self) -> (String, bool) {
+        self.literal_affix(false)
+    }
+
+    pub(crate) fn literal_suffix(&self) -> (String, bool) {
+        self.literal_affix(true)
+    }
+
+    fn literal_affix(&self, suffix: bool) -> (String, bool) {
+        match self {
+            Ast::Empty => (String::new(), true),
+            Ast::Class(ranges) => match ranges.as_slice() {
+                [range] if range.start() == range.end() => (range.start().to_string(), true),
+                _ => (String::new(), false),
+            },
+            Ast::Concat(items) => {
+                let mut affix = String::new();
+                let items: Box<dyn Iterator<Item = &Ast>> = if suffix {
+                    Box::new(items.iter().rev())
+                } else {
+                    Box::new(items.iter())
+                };
+                for item in items {
+                    let (item_affix, exact) = item.literal_affix(suffix);
+                    if suffix {
+                        affix.insert_str(0, &item_affix);
+                    } else {
+                        affix.push_str(&item_affix);
+                    }
+                    if !exact {
+                        return (affix, false);
+                    }
+                }
+                (affix, true)
+            }
+            Ast::Alternation(alternatives) => {
+                let affixes: Vec<(String, bool)> = alternatives
+                    .iter()
+                    .map(|alternative| alternative.literal_affix(suffix))
+                    .collect();
+                let exact = affixes
+                    .iter()
+                    .all(|(affix, exact)| *exact && *affix == affixes[0].0);
+                let common = affixes
+                    .iter()
+                    .map(|(affix, _)| affix.clone())
+                    .reduce(|common, affix| common_affix(&common, &affix, suffix))
+                    .unwrap_or_default();
+                (common, exact)
+            }
+            Ast::Repeat { inner, min, max } => {
+                if *min == 0 {
+                    return (String::new(), *max == Some(0));
+                }
+                match inner.literal_affix(suffix) {
+                    (affix, true) => (affix.repeat(*min), *max == Some(*min)),
+                    (affix, false) => (affix, false),
+                }
+            }
+            Ast::Capture { inner, .. } => inner.literal_affix(suffix),
+        }
+    }
+
+    // The longest literal that occurs in every match.
+    pub(crate) fn required_literal(&self) -> String {
+        match self {
+            Ast::Empty | Ast::Class(_) => self.literal_prefix().0,
+            Ast::Concat(items) => {
+                let mut required = String::new();
+                let mut run = String::new();
+                for item in items {
+                    let (prefix, exact) = item.literal_prefix();
+                    run.push_str(&prefix);
+                    if exact {
+                        continue;
+                    }
+
+                    required = longest(required, run);
+                    required = longest(required, item.required_literal());
+                    run = item.literal_suffix().0;
+                }
+                longest(required, run)
+            }
+            Ast::Alternation(alternatives) => {
+                let required: Vec<String> = alternatives
+                    .iter()
+                    .map(|alternative| alternative.required_literal())
+                    .collect();
+                match required.split_first() {
+                    Some((first, rest)) if rest.iter().all(|literal| literal == first) => {
+                        first.clone()
+                    }
+                    _ => String::new(),
+                }
+            }
+            Ast::Repeat { inner, min, .. } => {
+                if *min == 0 {
+                    return String::new();
+                }
+                match inner.literal_prefix() {
+                    (literal, true) => literal.repeat(*min),
+                    _ => inner.required_literal(),
+                }
+            }
+            Ast::Capture { inner, .. } => inner.required_literal(),
+        }
+    }
+}
+
+fn common_affix(left: &str, right: &str, suffix: bool) -> String {
+    if suffix {
+        let common: Vec<char> = left
+            .chars()
+            .rev()
+            .zip(right.chars().rev())
+            .take_while(|(l, r)| l == r)
+            .map(|(l, _)| l)
+            .collect();
+        common.into_iter().rev().collect()
+    } else {
+        left.chars()
+            .zip(right.chars())
+            .take_while(|(l, r)| l == r)
+            .map(|(l, _)| l)
+            .collect()
+    }
+}
+
+fn longest(left: String, right: String) -> String {
+    if right.len() > left.len() {
+        right
+    } else {
+        left
+    }
+}
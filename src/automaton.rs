@@ -0,0 +1,62 @@
+use std::{collections::HashSet, hash::Hash};
+
+use crate::{StateId, symbol::Symbol};
+
+// The part every automaton shares, so matching and graph algorithms can be
+// written once for all of them.
+pub trait Automaton {
+    type Symbol: Symbol;
+
+    // What an edge consumes.
+    type Label<'a>
+    where
+        Self: 'a;
+
+    // The states the automaton can be in at once while reading, along with
+    // anything else it needs to remember between symbols.
+    type States: Clone + Eq + Hash;
+
+    fn state_count(&self) -> usize;
+
+    fn start(&self) -> StateId;
+
+    fn is_accepting_state(&self, state: StateId) -> bool;
+
+    fn transitions(&self) -> impl Iterator<Item = (StateId, Self::Label<'_>, StateId)> + '_;
+
+    fn start_states(&self) -> Self::States;
+
+    fn step(&self, states: &mut Self::States, c: Self::Symbol);
+
+    fn is_accepting(&self, states: &Self::States) -> bool;
+
+    fn accepts<I: IntoIterator<Item = Self::Symbol>>(&self, input: I) -> bool {
+        let mut states = self.start_states();
+        for c in input {
+            self.step(&mut states, c);
+        }
+        self.is_accepting(&states)
+    }
+
+    // No accepting state can be reached from the start. Labels are not looked
+    // at, so an edge is taken as being passable.
+    fn is_empty(&self) -> bool {
+        let mut visited: HashSet<StateId> = HashSet::from([self.start()]);
+        let mut stack: Vec<StateId> = vec![self.start()];
+        let mut edges: Vec<Vec<StateId>> = vec![Vec::new(); self.state_count()];
+        for (from, _, to) in self.transitions() {
+            edges[from].push(to);
+        }
+        while let Some(state) = stack.pop() {
+            if self.is_accepting_state(state) {
+                return false;
+            }
+            for &next in &edges[state] {
+                if visited.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        true
+    }
+}
@@ -1,61 +1,367 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    ops::RangeInclusive,
+    sync::OnceLock,
+};
 
 use crate::{
     Alphabet, StateId, StatePair,
+    automaton::Automaton,
+    engine::EngineKind,
+    error::{MatchKindError, SymbolBuildError},
     minimized_dfa::{MinimizedDfa, MinimizedDfaBuilder},
+    nfa::Nfa,
+    otherwise::{Otherwise, own_edges},
+    search::{
+        Anchors, Match, MatchKind, ReverseSearcher, Searcher, Threads, find_leftmost,
+        rfind_reversed,
+    },
+    stream::StreamMatcher,
+    symbol::Symbol,
 };
 
-type DFATransition = HashMap<Alphabet, StateId>;
+type DFATransition<A = Alphabet> = HashMap<A, StateId>;
 
-pub struct DfaBuilder {
-    pub transitions: Vec<DFATransition>,
+// State 0 is the dead state every missing transition leads to, so the states
+// added start at 1.
+pub struct DfaBuilder<A = Alphabet> {
+    pub(crate) transitions: Vec<DFATransition<A>>,
+    start: Option<StateId>,
+    end: HashSet<StateId>,
 }
 
-impl DfaBuilder {
-    pub fn new() -> Self {
+impl<A: Symbol> Default for DfaBuilder<A> {
+    fn default() -> Self {
         Self {
-            transitions: Vec::new(),
+            transitions: vec![DFATransition::new()],
+            start: None,
+            end: HashSet::new(),
         }
     }
+}
+
+impl DfaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
+impl<A: Symbol> DfaBuilder<A> {
     pub fn add_state(&mut self) -> StateId {
         let state: StateId = self.transitions.len();
         self.transitions.push(DFATransition::new());
         state
     }
+
+    pub fn add_transition(
+        &mut self,
+        from: StateId,
+        c: A,
+        to: StateId,
+    ) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(from)?;
+        self.check_state(to)?;
+        match self.transitions[from].get(&c) {
+            Some(&next) if next != to => Err(SymbolBuildError::Nondeterministic {
+                state: from,
+                symbol: c,
+            }),
+            _ => {
+                self.transitions[from].insert(c, to);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn set_start(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        self.start = Some(state);
+        Ok(())
+    }
+
+    pub fn set_accepting(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        self.end.insert(state);
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<Dfa<A>, SymbolBuildError<A>> {
+        let start = self.start.ok_or(SymbolBuildError::MissingStart)?;
+        Ok(Dfa::new(self.transitions, start, self.end))
+    }
+
+    fn check_state(&self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        if state != 0 && state < self.transitions.len() {
+            Ok(())
+        } else {
+            Err(SymbolBuildError::UnknownState(state))
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct Dfa {
-    transitions: Vec<DFATransition>,
+pub struct Dfa<A = Alphabet> {
+    transitions: Vec<DFATransition<A>>,
     start: StateId,
     end: HashSet<StateId>,
+    otherwise: Otherwise<A>,
+    match_kind: MatchKind,
+    reversed: OnceLock<Box<Dfa<A>>>,
 }
 
-impl Dfa {
-    pub fn new(transitions: Vec<DFATransition>, start: StateId, end: HashSet<StateId>) -> Self {
+impl<A: Symbol> Dfa<A> {
+    pub fn new(transitions: Vec<DFATransition<A>>, start: StateId, end: HashSet<StateId>) -> Self {
         Self {
             transitions,
             start,
             end,
+            otherwise: Otherwise::default(),
+            match_kind: MatchKind::default(),
+            reversed: OnceLock::new(),
         }
     }
 
-    pub fn is_match(&self, input: &str) -> bool {
-        let mut curr: StateId = self.start;
-        for c in input.chars() {
-            if let Some(&next) = self.transitions[curr].get(&c) {
-                curr = next;
-            } else {
-                return false;
+    pub(crate) fn with_otherwise(mut self, otherwise: Otherwise<A>) -> Self {
+        self.otherwise = otherwise;
+        self
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn start(&self) -> StateId {
+        self.start
+    }
+
+    pub fn next_state(&self, state: StateId, c: A) -> Option<StateId> {
+        match self.transitions[state].get(&c) {
+            Some(&next) => Some(next),
+            None => self.otherwise.next_state(state, c),
+        }
+    }
+
+    // The states one edge away, without going through the symbols of the
+    // default edge one at a time.
+    fn successors(&self, state: StateId) -> impl Iterator<Item = StateId> + '_ {
+        self.transitions[state]
+            .values()
+            .copied()
+            .chain(self.otherwise.target(state))
+    }
+
+    pub fn is_accepting_state(&self, state: StateId) -> bool {
+        self.end.contains(&state)
+    }
+
+    pub fn accepting_states(&self) -> impl Iterator<Item = StateId> + '_ {
+        let mut states: Vec<StateId> = self.end.iter().copied().collect();
+        states.sort_unstable();
+        states.into_iter()
+    }
+
+    // Every edge as (from, symbol, to), ordered by state and then by symbol.
+    // A default edge is listed once for every symbol it is taken on.
+    pub fn transitions(&self) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+        self.transitions
+            .iter()
+            .enumerate()
+            .flat_map(|(from, transitions)| {
+                self.otherwise
+                    .edges(from, transitions)
+                    .map(move |(c, to)| (from, c, to))
+            })
+    }
+
+    // The same automaton with every default edge spelled out symbol by symbol.
+    fn materialized(&self) -> Dfa<A> {
+        let mut transitions: Vec<DFATransition<A>> =
+            vec![DFATransition::new(); self.transitions.len()];
+        for (from, c, to) in self.transitions() {
+            transitions[from].insert(c, to);
+        }
+        Dfa::new(transitions, self.start, self.end.clone())
+    }
+
+    // Concatenation and the repetitions go through the NFA and are
+    // determinized again.
+    fn to_nfa(&self) -> Nfa<A> {
+        Nfa::from_edges(
+            self.transitions.len(),
+            own_edges(&self.transitions),
+            self.start,
+            &self.end,
+        )
+        .with_defaults(self.otherwise.default_edges(&self.transitions))
+    }
+
+    pub fn concat(&self, other: &Dfa<A>) -> Dfa<A> {
+        self.to_nfa().concat(&other.to_nfa()).to_dfa()
+    }
+
+    pub fn star(&self) -> Dfa<A> {
+        self.to_nfa().star().to_dfa()
+    }
+
+    pub fn plus(&self) -> Dfa<A> {
+        self.to_nfa().plus().to_dfa()
+    }
+
+    pub fn optional(&self) -> Dfa<A> {
+        self.to_nfa().optional().to_dfa()
+    }
+
+    // The automaton running both at once, accepting wherever `accept` does
+    // given whether each side accepts. A side whose transition is missing is
+    // dead from then on. The symbols neither side has an edge of its own for
+    // are followed through one default edge, which needs both sides to default
+    // over the same symbols.
+    fn product(&self, other: &Dfa<A>, accept: fn(bool, bool) -> bool) -> Dfa<A> {
+        type Pair = (Option<StateId>, Option<StateId>);
+
+        if !self.otherwise.is_empty()
+            && !other.otherwise.is_empty()
+            && self.otherwise.domain() != other.otherwise.domain()
+        {
+            return self.product(&other.materialized(), accept);
+        }
+        let domain = if self.otherwise.is_empty() {
+            other.otherwise.domain()
+        } else {
+            self.otherwise.domain()
+        };
+        let mut otherwise = Otherwise::new(domain);
+
+        let is_end = |(left, right): Pair| {
+            accept(
+                left.is_some_and(|state| self.end.contains(&state)),
+                right.is_some_and(|state| other.end.contains(&state)),
+            )
+        };
+
+        let mut dfa_builder = DfaBuilder::default();
+        let mut dfa_end: HashSet<StateId> = HashSet::new();
+        let mut pair_to_dfa_state: HashMap<Pair, StateId> = HashMap::new();
+
+        let start: Pair = (Some(self.start), Some(other.start));
+        let dfa_start = dfa_builder.add_state();
+        if is_end(start) {
+            dfa_end.insert(dfa_start);
+        }
+        pair_to_dfa_state.insert(start, dfa_start);
+
+        let mut queue: VecDeque<Pair> = VecDeque::from([start]);
+        while let Some(pair @ (left, right)) = queue.pop_front() {
+            let curr_dfa_state = pair_to_dfa_state[&pair];
+            let mut symbols: Vec<A> = left
+                .into_iter()
+                .flat_map(|state| self.transitions[state].keys())
+                .chain(
+                    right
+                        .into_iter()
+                        .flat_map(|state| other.transitions[state].keys()),
+                )
+                .copied()
+                .collect();
+            symbols.sort_unstable();
+            symbols.dedup();
+
+            // None stands for the symbols of the default edge, if it is taken
+            // on any.
+            let by_default: Pair = (
+                left.and_then(|state| self.otherwise.target(state)),
+                right.and_then(|state| other.otherwise.target(state)),
+            );
+            let has_default = otherwise
+                .symbols(|c| symbols.binary_search(c).is_ok())
+                .next()
+                .is_some();
+            for c in symbols
+                .iter()
+                .copied()
+                .map(Some)
+                .chain(has_default.then_some(None))
+            {
+                let next: Pair = match c {
+                    Some(c) => (
+                        left.and_then(|state| self.next_state(state, c)),
+                        right.and_then(|state| other.next_state(state, c)),
+                    ),
+                    None => by_default,
+                };
+                // Both sides are dead, which no operation accepts.
+                if next == (None, None) {
+                    continue;
+                }
+                let next_dfa_state = match pair_to_dfa_state.get(&next) {
+                    Some(&dfa_state) => dfa_state,
+                    None => {
+                        let state = dfa_builder.add_state();
+                        if is_end(next) {
+                            dfa_end.insert(state);
+                        }
+                        pair_to_dfa_state.insert(next, state);
+                        queue.push_back(next);
+                        state
+                    }
+                };
+                match c {
+                    Some(c) => {
+                        dfa_builder.transitions[curr_dfa_state].insert(c, next_dfa_state);
+                    }
+                    None => otherwise.insert(curr_dfa_state, next_dfa_state),
+                }
             }
         }
 
-        self.end.contains(&curr)
+        Dfa::new(dfa_builder.transitions, dfa_start, dfa_end).with_otherwise(otherwise)
+    }
+
+    // Every string over the symbols of `universe`, through a single default
+    // edge rather than an edge for each symbol.
+    fn universal(universe: &[RangeInclusive<A>]) -> Dfa<A> {
+        let mut dfa_builder = DfaBuilder::default();
+        let state = dfa_builder.add_state();
+        let mut otherwise = Otherwise::new(universe);
+        otherwise.insert(state, state);
+        Dfa::new(dfa_builder.transitions, state, HashSet::from([state])).with_otherwise(otherwise)
+    }
+
+    pub fn intersect(&self, other: &Dfa<A>) -> Dfa<A> {
+        self.product(other, |left, right| left && right)
+    }
+
+    pub fn union(&self, other: &Dfa<A>) -> Dfa<A> {
+        self.product(other, |left, right| left || right)
+    }
+
+    pub fn difference(&self, other: &Dfa<A>) -> Dfa<A> {
+        self.product(other, |left, right| left && !right)
+    }
+
+    pub fn symmetric_difference(&self, other: &Dfa<A>) -> Dfa<A> {
+        self.product(other, |left, right| left != right)
+    }
+
+    // The strings over the symbols of `universe` this automaton rejects. Use
+    // `'\0'..=char::MAX` for all of Unicode.
+    pub fn complement(&self, universe: &[RangeInclusive<A>]) -> Dfa<A> {
+        Dfa::universal(universe).difference(self)
+    }
+
+    pub fn reverse(&self) -> Dfa<A> {
+        Nfa::from_reversed_edges(
+            self.transitions.len(),
+            own_edges(&self.transitions),
+            &self.otherwise.default_edges(&self.transitions),
+            self.start,
+            &self.end,
+        )
+        .to_dfa()
     }
 
-    pub fn to_minimized_dfa(&self) -> MinimizedDfa {
-        let mut minimized_dfa_builder = MinimizedDfaBuilder::new();
+    pub fn to_minimized_dfa(&self) -> MinimizedDfa<A> {
+        let mut minimized_dfa_builder = MinimizedDfaBuilder::default();
 
         let total_dfa_states = self.transitions.len();
 
@@ -64,7 +370,7 @@ impl Dfa {
         while !current_states.is_empty() {
             let mut next_states: HashSet<StateId> = HashSet::new();
             for &state in &current_states {
-                for (&_alphabet, &next) in &self.transitions[state] {
+                for next in self.successors(state) {
                     if !reachable_states.contains(&next) {
                         next_states.insert(next);
                     }
@@ -85,7 +391,7 @@ impl Dfa {
                     end_state_reachable = true;
                     break;
                 }
-                for (&_alphabet, &next) in &self.transitions[curr] {
+                for next in self.successors(curr) {
                     if !current_states.contains(&next) {
                         current_states.insert(next);
                         stack.push(next);
@@ -112,15 +418,38 @@ impl Dfa {
             }
         }
 
+        let alphabet: BTreeSet<A> = self
+            .transitions
+            .iter()
+            .flat_map(|transitions| transitions.keys())
+            .copied()
+            .collect();
+        // None stands for the symbols of the default edge no state has an edge
+        // of its own for, all of which go the same way from every state.
+        let by_default = self
+            .otherwise
+            .symbols(|c| alphabet.contains(c))
+            .next()
+            .is_some();
+        let columns: Vec<Option<A>> = alphabet
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(by_default.then_some(None))
+            .collect();
+
         loop {
             let mut change = false;
-            for c in u8::MIN..=u8::MAX {
-                let c = c as char;
+            for &c in &columns {
                 let mut group_to_states: BTreeMap<StatePair, HashSet<StateId>> = BTreeMap::new();
                 for &curr in group_mapping.keys() {
-                    let next = match self.transitions[curr].get(&c) {
-                        Some(&next) => next,
-                        None => 0,
+                    let next = match c {
+                        Some(c) => self.next_state(curr, c),
+                        None => self.otherwise.target(curr),
+                    };
+                    let next = match next {
+                        Some(next) if group_mapping.contains_key(&next) => next,
+                        _ => 0,
                     };
                     group_to_states
                         .entry((group_mapping[&curr], group_mapping[&next]))
@@ -147,23 +476,33 @@ impl Dfa {
             }
         }
 
-        minimized_dfa_builder.add_state();
-
         let largest_node = group_mapping
             .values()
             .max()
             .expect("there must be some nodes in minimized_dfa_builder");
 
-        while minimized_dfa_builder.add_state() != *largest_node {}
+        while minimized_dfa_builder.transitions.len() <= *largest_node {
+            minimized_dfa_builder.add_state();
+        }
+
+        let mut default_targets: HashMap<StateId, StateId> = HashMap::new();
+        for (&dfa_state, &group) in &group_mapping {
+            let target = self.otherwise.target(dfa_state);
+            if let Some(&next_group) = target.and_then(|next| group_mapping.get(&next)) {
+                default_targets.insert(group, next_group);
+            }
+        }
 
         let mut minimized_dfa_start = 0;
         let mut minimized_dfa_end: HashSet<StateId> = HashSet::new();
         for (&dfa_state, &group) in &group_mapping {
-            for c in u8::MIN..=u8::MAX {
-                let c = c as char;
-                if let Some(next_dfa_state) = self.transitions[dfa_state].get(&c) {
-                    minimized_dfa_builder.transitions[group]
-                        .insert(c, group_mapping[next_dfa_state]);
+            for (&c, next_dfa_state) in &self.transitions[dfa_state] {
+                if let Some(&next_group) = group_mapping.get(next_dfa_state) {
+                    minimized_dfa_builder.transitions[group].insert(c, next_group);
+                } else if default_targets.contains_key(&group) {
+                    // Leaving the edge out would send the symbol down the
+                    // default edge instead, so it goes to the dead state.
+                    minimized_dfa_builder.transitions[group].insert(c, 0);
                 }
             }
 
@@ -176,10 +515,154 @@ impl Dfa {
             }
         }
 
+        // A default edge every symbol of which has an edge of its own is never
+        // taken, and would keep equivalent automata apart.
+        let mut otherwise = Otherwise::new(self.otherwise.domain());
+        for (group, next_group) in default_targets {
+            let transitions = &minimized_dfa_builder.transitions[group];
+            if otherwise
+                .symbols(|c| transitions.contains_key(c))
+                .next()
+                .is_some()
+            {
+                otherwise.insert(group, next_group);
+            }
+        }
+
         MinimizedDfa::new(
             minimized_dfa_builder.transitions,
             minimized_dfa_start,
             minimized_dfa_end,
         )
+        .with_otherwise(otherwise)
+    }
+}
+
+impl Dfa {
+    pub fn with_match_kind(mut self, match_kind: MatchKind) -> Result<Self, MatchKindError> {
+        if match_kind == MatchKind::LeftmostFirst {
+            return Err(MatchKindError {
+                engine: EngineKind::Dfa,
+                match_kind,
+            });
+        }
+        self.match_kind = match_kind;
+        Ok(self)
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_dfa(self)
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        let mut curr: StateId = self.start;
+        for c in input.chars() {
+            if let Some(next) = self.next_state(curr, c) {
+                curr = next;
+            } else {
+                return false;
+            }
+        }
+
+        self.end.contains(&curr)
+    }
+
+    pub(crate) fn find_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        find_leftmost(self, haystack, start, None, self.match_kind, anchors)
+    }
+
+    pub(crate) fn rfind_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        end: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let reversed = self.reversed.get_or_init(|| Box::new(self.reverse()));
+        rfind_reversed(haystack, end, anchors, |haystack, start, anchors| {
+            reversed.find_anchored_at(haystack, start, anchors)
+        })
+    }
+}
+
+impl Threads for Dfa {
+    type State = StateId;
+    type Held = HashSet<StateId>;
+
+    fn start_state(&self) -> StateId {
+        self.start
+    }
+
+    fn next_states(&self, state: StateId, c: char, push: impl FnMut(StateId)) {
+        self.next_state(state, c).into_iter().for_each(push);
+    }
+
+    fn hold(&self, held: &mut HashSet<StateId>, state: StateId) -> Option<StateId> {
+        held.insert(state).then_some(state)
+    }
+
+    fn release(&self, held: &mut HashSet<StateId>) {
+        held.clear();
+    }
+
+    fn thread_accepts(&self, state: StateId) -> bool {
+        self.end.contains(&state)
+    }
+}
+
+impl Searcher for Dfa {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        self.find_anchored_at(haystack, start, &Anchors::default())
+    }
+}
+
+impl ReverseSearcher for Dfa {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        self.rfind_anchored_at(haystack, end, &Anchors::default())
+    }
+}
+
+impl<A: Symbol> Automaton for Dfa<A> {
+    type Symbol = A;
+    type Label<'a>
+        = A
+    where
+        A: 'a;
+    type States = Option<StateId>;
+
+    fn state_count(&self) -> usize {
+        Dfa::state_count(self)
+    }
+
+    fn start(&self) -> StateId {
+        Dfa::start(self)
+    }
+
+    fn is_accepting_state(&self, state: StateId) -> bool {
+        Dfa::is_accepting_state(self, state)
+    }
+
+    fn transitions(&self) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+        Dfa::transitions(self)
+    }
+
+    fn start_states(&self) -> Option<StateId> {
+        Some(self.start)
+    }
+
+    fn step(&self, states: &mut Option<StateId>, c: A) {
+        *states = states.and_then(|state| self.next_state(state, c));
+    }
+
+    fn is_accepting(&self, states: &Option<StateId>) -> bool {
+        states.is_some_and(|state| self.end.contains(&state))
     }
 }
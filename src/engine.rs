@@ -0,0 +1,210 @@
+use std::io::{BufRead, Read};
+
+use crate::{
+    aho_corasick::AhoCorasick,
+    dfa::Dfa,
+    epsilon_nfa::EpsilonNfa,
+    glushkov::Glushkov,
+    lazy_dfa::LazyDfa,
+    literal::Literal,
+    minimized_dfa::MinimizedDfa,
+    nfa::Nfa,
+    pike_vm,
+    reader::{LineMatches, ReaderFindIter},
+    search::{Anchors, Input, Match, MatchKind, ReverseSearcher, Searcher, find_leftmost_in},
+    stream::StreamMatcher,
+};
+
+#[derive(Debug)]
+pub enum Engine {
+    Literal(Literal),
+    LiteralSet(AhoCorasick),
+    Glushkov(Glushkov),
+    EpsilonNfa(EpsilonNfa),
+    Nfa(Nfa),
+    LazyDfa(LazyDfa),
+    Dfa(Dfa),
+    MinimizedDfa(MinimizedDfa),
+}
+
+// The automata a pattern can be forced to compile to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EngineKind {
+    Glushkov,
+    EpsilonNfa,
+    Nfa,
+    LazyDfa,
+    Dfa,
+    MinimizedDfa,
+}
+
+impl Engine {
+    pub fn is_match(&self, input: &str) -> bool {
+        match self {
+            Engine::Literal(literal) => literal.is_match(input),
+            Engine::LiteralSet(aho_corasick) => aho_corasick.is_match(input),
+            Engine::Glushkov(glushkov) => glushkov.is_match(input),
+            Engine::EpsilonNfa(epsilon_nfa) => epsilon_nfa.is_match(input),
+            Engine::Nfa(nfa) => nfa.is_match(input),
+            Engine::LazyDfa(lazy_dfa) => lazy_dfa.is_match(input),
+            Engine::Dfa(dfa) => dfa.is_match(input),
+            Engine::MinimizedDfa(minimized_dfa) => minimized_dfa.is_match(input),
+        }
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        match self {
+            Engine::Literal(literal) => literal.stream(),
+            Engine::LiteralSet(aho_corasick) => aho_corasick.stream(),
+            Engine::Glushkov(glushkov) => glushkov.stream(),
+            Engine::EpsilonNfa(epsilon_nfa) => epsilon_nfa.stream(),
+            Engine::Nfa(nfa) => nfa.stream(),
+            Engine::LazyDfa(lazy_dfa) => lazy_dfa.stream(),
+            Engine::Dfa(dfa) => dfa.stream(),
+            Engine::MinimizedDfa(minimized_dfa) => minimized_dfa.stream(),
+        }
+    }
+
+    // Searches a reader of UTF-8 text in one pass, keeping in memory only the
+    // input from the earliest start a match may still have.
+    pub fn find_iter_in_reader<R: Read>(&self, reader: R) -> ReaderFindIter<'_, R> {
+        ReaderFindIter::new(self, reader, Anchors::default())
+    }
+
+    pub fn find_lines<R: BufRead>(&self, reader: R) -> LineMatches<'_, R> {
+        LineMatches::new(self, reader)
+    }
+
+    // Finds the same match as find_anchored_at, running every engine as threads
+    // over input that may still have to be read.
+    pub(crate) fn find_in<I: Input + ?Sized>(
+        &self,
+        input: &mut I,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Result<Option<(usize, usize)>, I::Error> {
+        match self {
+            Engine::Literal(literal) => find_leftmost_in(
+                literal,
+                input,
+                start,
+                None,
+                MatchKind::LeftmostLongest,
+                anchors,
+            ),
+            Engine::LiteralSet(aho_corasick) => find_leftmost_in(
+                aho_corasick,
+                input,
+                start,
+                None,
+                aho_corasick.match_kind(),
+                anchors,
+            ),
+            Engine::Glushkov(glushkov) => find_leftmost_in(
+                glushkov,
+                input,
+                start,
+                glushkov.prefilter(),
+                glushkov.match_kind(),
+                anchors,
+            ),
+            Engine::EpsilonNfa(epsilon_nfa) => {
+                pike_vm::find_in(epsilon_nfa, input, start, epsilon_nfa.match_kind(), anchors)
+            }
+            Engine::Nfa(nfa) => {
+                find_leftmost_in(nfa, input, start, None, nfa.match_kind(), anchors)
+            }
+            Engine::LazyDfa(lazy_dfa) => lazy_dfa.find_in(input, start, anchors),
+            Engine::Dfa(dfa) => {
+                find_leftmost_in(dfa, input, start, None, dfa.match_kind(), anchors)
+            }
+            Engine::MinimizedDfa(minimized_dfa) => find_leftmost_in(
+                minimized_dfa,
+                input,
+                start,
+                minimized_dfa.prefilter(),
+                minimized_dfa.match_kind(),
+                anchors,
+            ),
+        }
+    }
+
+    // The literal engines are only picked for patterns without anchors.
+    pub(crate) fn find_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        match self {
+            Engine::Literal(_) | Engine::LiteralSet(_) => {
+                debug_assert!(anchors.is_empty(), "literal engines ignore anchors");
+                self.find_at(haystack, start)
+            }
+            Engine::Glushkov(glushkov) => glushkov.find_anchored_at(haystack, start, anchors),
+            Engine::MinimizedDfa(minimized_dfa) => {
+                minimized_dfa.find_anchored_at(haystack, start, anchors)
+            }
+            Engine::EpsilonNfa(epsilon_nfa) => {
+                epsilon_nfa.find_anchored_at(haystack, start, anchors)
+            }
+            Engine::Nfa(nfa) => nfa.find_anchored_at(haystack, start, anchors),
+            Engine::LazyDfa(lazy_dfa) => lazy_dfa.find_anchored_at(haystack, start, anchors),
+            Engine::Dfa(dfa) => dfa.find_anchored_at(haystack, start, anchors),
+        }
+    }
+
+    pub(crate) fn rfind_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        end: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        match self {
+            Engine::Literal(_) | Engine::LiteralSet(_) => {
+                debug_assert!(anchors.is_empty(), "literal engines ignore anchors");
+                self.rfind_at(haystack, end)
+            }
+            Engine::Glushkov(glushkov) => glushkov.rfind_anchored_at(haystack, end, anchors),
+            Engine::MinimizedDfa(minimized_dfa) => {
+                minimized_dfa.rfind_anchored_at(haystack, end, anchors)
+            }
+            Engine::EpsilonNfa(epsilon_nfa) => {
+                epsilon_nfa.rfind_anchored_at(haystack, end, anchors)
+            }
+            Engine::Nfa(nfa) => nfa.rfind_anchored_at(haystack, end, anchors),
+            Engine::LazyDfa(lazy_dfa) => lazy_dfa.rfind_anchored_at(haystack, end, anchors),
+            Engine::Dfa(dfa) => dfa.rfind_anchored_at(haystack, end, anchors),
+        }
+    }
+}
+
+impl Searcher for Engine {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        match self {
+            Engine::Literal(literal) => literal.find_at(haystack, start),
+            Engine::LiteralSet(aho_corasick) => aho_corasick.find_at(haystack, start),
+            Engine::Glushkov(glushkov) => glushkov.find_at(haystack, start),
+            Engine::MinimizedDfa(minimized_dfa) => minimized_dfa.find_at(haystack, start),
+            Engine::EpsilonNfa(epsilon_nfa) => epsilon_nfa.find_at(haystack, start),
+            Engine::Nfa(nfa) => nfa.find_at(haystack, start),
+            Engine::LazyDfa(lazy_dfa) => lazy_dfa.find_at(haystack, start),
+            Engine::Dfa(dfa) => dfa.find_at(haystack, start),
+        }
+    }
+}
+
+impl ReverseSearcher for Engine {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        match self {
+            Engine::Literal(literal) => literal.rfind_at(haystack, end),
+            Engine::LiteralSet(aho_corasick) => aho_corasick.rfind_at(haystack, end),
+            Engine::Glushkov(glushkov) => glushkov.rfind_at(haystack, end),
+            Engine::MinimizedDfa(minimized_dfa) => minimized_dfa.rfind_at(haystack, end),
+            Engine::EpsilonNfa(epsilon_nfa) => epsilon_nfa.rfind_at(haystack, end),
+            Engine::Nfa(nfa) => nfa.rfind_at(haystack, end),
+            Engine::LazyDfa(lazy_dfa) => lazy_dfa.rfind_at(haystack, end),
+            Engine::Dfa(dfa) => dfa.rfind_at(haystack, end),
+        }
+    }
+}
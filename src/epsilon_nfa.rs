@@ -1,246 +1,796 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    mem,
+    ops::RangeInclusive,
+    sync::OnceLock,
+};
 
 use crate::{
-    StateId, StatePair,
+    Alphabet, StateId,
+    automaton::Automaton,
+    error::SymbolBuildError,
+    limits::{DfaError, DfaLimits},
     nfa::{Nfa, NfaBuilder},
+    pike_vm,
+    search::{Anchors, Match, MatchKind, ReverseSearcher, Searcher, rfind_reversed},
+    sparse_set::{Cache, SparseSet},
+    stream::StreamMatcher,
+    symbol::{Symbol, range_symbols},
 };
 
-#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
-pub enum Alphabet {
-    Char(char),
+const HOLE: StateId = StateId::MAX;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum State<A = Alphabet> {
+    Range {
+        ranges: Vec<RangeInclusive<A>>,
+        next: StateId,
+    },
+    Counted {
+        ranges: Vec<RangeInclusive<A>>,
+        min: usize,
+        max: Option<usize>,
+        next: StateId,
+    },
+    Split {
+        alternatives: Vec<StateId>,
+    },
+    Epsilon {
+        next: StateId,
+    },
+    Match,
+}
+
+// What an edge of the εNFA consumes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Label<'a, A = Alphabet> {
+    Ranges(&'a [RangeInclusive<A>]),
     Epsilon,
 }
-type NFATransition = HashMap<Alphabet, HashSet<StateId>>;
 
-pub struct EpsilonNfaBuilder {
-    pub transitions: Vec<NFATransition>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hole {
+    Next(StateId),
+    Alternative(StateId, usize),
 }
 
-impl EpsilonNfaBuilder {
-    pub fn new() -> Self {
+#[derive(Debug, Clone)]
+pub(crate) struct Fragment {
+    pub(crate) start: StateId,
+    holes: Vec<Hole>,
+}
+
+// Builds an εNFA either by hand, state by state, or from the fragments of a
+// Thompson construction.
+pub struct EpsilonNfaBuilder<A = Alphabet> {
+    states: Vec<State<A>>,
+    start: Option<StateId>,
+    end: Option<StateId>,
+}
+
+impl<A: Symbol> Default for EpsilonNfaBuilder<A> {
+    fn default() -> Self {
         Self {
-            transitions: Vec::new(),
+            states: Vec::new(),
+            start: None,
+            end: None,
         }
     }
+}
 
-    pub fn add_state(&mut self) -> StateId {
-        let state: StateId = self.transitions.len();
-        self.transitions.push(NFATransition::new());
-        state
+impl EpsilonNfaBuilder {
+    pub fn new() -> Self {
+        Self::default()
     }
+}
 
-    pub fn add_epsilon_transition(&mut self, from: StateId, to: StateId) {
-        self.transitions[from]
-            .entry(Alphabet::Epsilon)
-            .or_default()
-            .insert(to);
+impl<A: Symbol> EpsilonNfaBuilder<A> {
+    // A state added by hand is a split over its outgoing edges, which are tried
+    // in the order they were added.
+    pub fn add_state(&mut self) -> StateId {
+        self.push_state(State::Split {
+            alternatives: Vec::new(),
+        })
     }
 
-    pub fn add_transition(&mut self, from: StateId, c: char, to: StateId) {
-        self.transitions[from]
-            .entry(Alphabet::Char(c))
-            .or_default()
-            .insert(to);
+    pub fn add_transition(
+        &mut self,
+        from: StateId,
+        c: A,
+        to: StateId,
+    ) -> Result<(), SymbolBuildError<A>> {
+        self.add_range_transition(from, c..=c, to)
     }
 
-    pub fn add_transition_range<I: IntoIterator<Item = char>>(
+    pub fn add_range_transition(
         &mut self,
         from: StateId,
-        chars: I,
+        range: RangeInclusive<A>,
         to: StateId,
-    ) {
-        for c in chars {
-            self.transitions[from]
-                .entry(Alphabet::Char(c))
-                .or_default()
-                .insert(to);
-        }
+    ) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(from)?;
+        self.check_state(to)?;
+        let edge = self.push_state(State::Range {
+            ranges: vec![range],
+            next: to,
+        });
+        self.push_alternative(from, edge);
+        Ok(())
     }
 
-    pub fn add_union_transition(&mut self, up: StatePair, down: StatePair) -> StatePair {
-        let start: StateId = self.add_state();
-        let end: StateId = self.add_state();
+    pub fn add_epsilon_transition(
+        &mut self,
+        from: StateId,
+        to: StateId,
+    ) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(from)?;
+        self.check_state(to)?;
+        self.push_alternative(from, to);
+        Ok(())
+    }
 
-        let (up_start, up_end) = up;
-        let (down_start, down_end) = down;
+    pub fn set_start(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        self.start = Some(state);
+        Ok(())
+    }
 
-        self.add_epsilon_transition(start, up_start);
-        self.add_epsilon_transition(start, down_start);
-        self.add_epsilon_transition(up_end, end);
-        self.add_epsilon_transition(down_end, end);
+    // Accepting states share the single match state through an epsilon edge.
+    pub fn set_accepting(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        let end = self.end_state();
+        if let State::Split { alternatives } = &self.states[state]
+            && !alternatives.contains(&end)
+        {
+            self.push_alternative(state, end);
+        }
+        Ok(())
+    }
 
-        (start, end)
+    pub fn build(mut self) -> Result<EpsilonNfa<A>, SymbolBuildError<A>> {
+        let start = self.start.ok_or(SymbolBuildError::MissingStart)?;
+        let end = self.end_state();
+        Ok(EpsilonNfa::new(self.states, start, end))
     }
 
-    pub fn add_concat_transition(&mut self, left: StatePair, right: StatePair) -> StatePair {
-        let (left_start, left_end) = left;
-        let (right_start, right_end) = right;
+    fn check_state(&self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        match self.states.get(state) {
+            Some(State::Split { .. }) => Ok(()),
+            _ => Err(SymbolBuildError::UnknownState(state)),
+        }
+    }
 
-        self.add_epsilon_transition(left_end, right_start);
+    fn push_alternative(&mut self, from: StateId, to: StateId) {
+        if let State::Split { alternatives } = &mut self.states[from] {
+            alternatives.push(to);
+        }
+    }
 
-        (left_start, right_end)
+    fn end_state(&mut self) -> StateId {
+        match self.end {
+            Some(end) => end,
+            None => {
+                let end = self.push_state(State::Match);
+                self.end = Some(end);
+                end
+            }
+        }
     }
 
-    pub fn add_star_transition(&mut self, inner: StatePair) -> StatePair {
-        let (inner_start, inner_end) = inner;
+    fn push_state(&mut self, state: State<A>) -> StateId {
+        let id: StateId = self.states.len();
+        self.states.push(state);
+        id
+    }
 
-        let start = self.add_state();
-        let end = self.add_state();
+    fn patch(&mut self, holes: &[Hole], to: StateId) {
+        for &hole in holes {
+            match (hole, &mut self.states[hole_state(hole)]) {
+                (
+                    Hole::Next(_),
+                    State::Range { next, .. }
+                    | State::Counted { next, .. }
+                    | State::Epsilon { next },
+                ) => {
+                    *next = to;
+                }
+                (Hole::Alternative(_, index), State::Split { alternatives }) => {
+                    alternatives[index] = to;
+                }
+                _ => unreachable!("holes always point at a state with a matching slot"),
+            }
+        }
+    }
 
-        self.add_epsilon_transition(start, inner_start);
-        self.add_epsilon_transition(inner_end, end);
-        self.add_epsilon_transition(start, end);
-        self.add_epsilon_transition(inner_end, inner_start);
+    pub(crate) fn add_empty(&mut self) -> Fragment {
+        let start = self.push_state(State::Epsilon { next: HOLE });
+        Fragment {
+            start,
+            holes: vec![Hole::Next(start)],
+        }
+    }
 
-        (start, end)
+    pub(crate) fn add_transition_ranges(&mut self, ranges: Vec<RangeInclusive<A>>) -> Fragment {
+        let start = self.push_state(State::Range { ranges, next: HOLE });
+        Fragment {
+            start,
+            holes: vec![Hole::Next(start)],
+        }
     }
 
-    pub fn add_plus_transition(&mut self, inner: StatePair) -> StatePair {
-        let (inner_start, inner_end) = inner;
+    pub(crate) fn add_union_transition(&mut self, mut alternatives: Vec<Fragment>) -> Fragment {
+        if alternatives.len() == 1 {
+            return alternatives
+                .pop()
+                .expect("alternatives has exactly one element");
+        }
 
-        let start = self.add_state();
-        let end = self.add_state();
+        let start = self.push_state(State::Split {
+            alternatives: alternatives.iter().map(|fragment| fragment.start).collect(),
+        });
+        Fragment {
+            start,
+            holes: alternatives
+                .into_iter()
+                .flat_map(|fragment| fragment.holes)
+                .collect(),
+        }
+    }
 
-        self.add_epsilon_transition(start, inner_start);
-        self.add_epsilon_transition(inner_end, end);
-        self.add_epsilon_transition(inner_end, inner_start);
+    pub(crate) fn add_concat_transition(&mut self, left: Fragment, right: Fragment) -> Fragment {
+        self.patch(&left.holes, right.start);
 
-        (start, end)
+        Fragment {
+            start: left.start,
+            holes: right.holes,
+        }
     }
 
-    pub fn add_question_transition(&mut self, inner: StatePair) -> StatePair {
-        let (inner_start, inner_end) = inner;
+    pub(crate) fn add_star_transition(&mut self, inner: Fragment) -> Fragment {
+        let start = self.push_state(State::Split {
+            alternatives: vec![inner.start, HOLE],
+        });
+        self.patch(&inner.holes, start);
 
-        let start = self.add_state();
-        let end = self.add_state();
+        Fragment {
+            start,
+            holes: vec![Hole::Alternative(start, 1)],
+        }
+    }
 
-        self.add_epsilon_transition(start, inner_start);
-        self.add_epsilon_transition(inner_end, end);
-        self.add_epsilon_transition(start, end);
+    pub(crate) fn add_plus_transition(&mut self, inner: Fragment) -> Fragment {
+        let split = self.push_state(State::Split {
+            alternatives: vec![inner.start, HOLE],
+        });
+        self.patch(&inner.holes, split);
 
-        (start, end)
+        Fragment {
+            start: inner.start,
+            holes: vec![Hole::Alternative(split, 1)],
+        }
     }
 
-    pub fn make_deep_copy(&mut self, start: StateId, end: StateId) -> Result<StatePair, String> {
-        let mut mappings: HashMap<StateId, StateId> = HashMap::new();
-        mappings.insert(start, self.add_state());
+    pub(crate) fn add_question_transition(&mut self, inner: Fragment) -> Fragment {
+        let start = self.push_state(State::Split {
+            alternatives: vec![inner.start, HOLE],
+        });
 
-        let mut stack: Vec<StateId> = Vec::new();
-        stack.push(start);
-        while let Some(curr) = stack.pop() {
-            for (&alphabet, next_states) in &self.transitions[curr].clone() {
-                for &next in next_states {
-                    match mappings.get(&next) {
-                        Some(&_) => {}
-                        None => {
-                            mappings.insert(next, self.add_state());
-                            stack.push(next);
-                        }
-                    };
-                    match alphabet {
-                        Alphabet::Char(c) => {
-                            self.add_transition(mappings[&curr], c, mappings[&next]);
-                        }
-                        Alphabet::Epsilon => {
-                            self.add_epsilon_transition(mappings[&curr], mappings[&next])
-                        }
-                    }
-                }
-            }
+        let mut holes = inner.holes;
+        holes.push(Hole::Alternative(start, 1));
+        Fragment { start, holes }
+    }
+
+    pub(crate) fn add_counted_transition(
+        &mut self,
+        ranges: Vec<RangeInclusive<A>>,
+        min: usize,
+        max: Option<usize>,
+    ) -> Fragment {
+        let start = self.push_state(State::Counted {
+            ranges,
+            min,
+            max,
+            next: HOLE,
+        });
+        Fragment {
+            start,
+            holes: vec![Hole::Next(start)],
         }
+    }
 
-        let Some(&mappings_end) = mappings.get(&end) else {
-            return Err("Could not reach end in make_deep_copy()".to_string());
-        };
+    pub(crate) fn finish(mut self, fragment: Fragment) -> EpsilonNfa<A> {
+        let end = self.push_state(State::Match);
+        self.patch(&fragment.holes, end);
 
-        Ok((mappings[&start], mappings_end))
+        EpsilonNfa::new(self.states, fragment.start, end)
     }
 }
 
+fn hole_state(hole: Hole) -> StateId {
+    match hole {
+        Hole::Next(state) | Hole::Alternative(state, _) => state,
+    }
+}
+
+type Position = (StateId, usize);
+
+type PositionEdge<'a, A> = (&'a [RangeInclusive<A>], Position);
+
 #[derive(Debug, Clone)]
-pub struct EpsilonNfa {
-    transitions: Vec<NFATransition>,
+pub struct EpsilonNfa<A = Alphabet> {
+    states: Vec<State<A>>,
     start: StateId,
     end: StateId,
+    epsilon_closures: Vec<Vec<StateId>>,
+    match_kind: MatchKind,
+    reversed: OnceLock<Box<EpsilonNfa<A>>>,
 }
 
-impl EpsilonNfa {
-    pub fn new(transitions: Vec<NFATransition>, start: StateId, end: StateId) -> Self {
+impl<A: Symbol> EpsilonNfa<A> {
+    pub fn new(states: Vec<State<A>>, start: StateId, end: StateId) -> Self {
+        let mut epsilon_closures: Vec<Vec<StateId>> = Vec::with_capacity(states.len());
+        let mut visited = SparseSet::new(states.len());
+        let mut stack: Vec<StateId> = Vec::new();
+        for curr in 0..states.len() {
+            visited.clear();
+            stack.push(curr);
+            while let Some(state) = stack.pop() {
+                if !visited.insert(state) {
+                    continue;
+                }
+                match &states[state] {
+                    State::Epsilon { next } => stack.push(*next),
+                    State::Counted { min: 0, next, .. } => stack.push(*next),
+                    State::Split { alternatives } => stack.extend(alternatives.iter().rev()),
+                    State::Range { .. } | State::Counted { .. } | State::Match => {}
+                }
+            }
+            epsilon_closures.push(
+                visited
+                    .iter()
+                    .copied()
+                    .filter(|&state| {
+                        matches!(
+                            states[state],
+                            State::Range { .. } | State::Counted { .. } | State::Match
+                        )
+                    })
+                    .collect(),
+            );
+        }
+
         Self {
-            transitions,
+            states,
             start,
             end,
+            epsilon_closures,
+            match_kind: MatchKind::default(),
+            reversed: OnceLock::new(),
         }
     }
 
-    fn epsilon_closure(&self, curr: StateId, res: &mut HashSet<StateId>) {
-        res.insert(curr);
-        if let Some(next_states) = self.transitions[curr].get(&Alphabet::Epsilon) {
-            for &next in next_states {
-                if !res.contains(&next) {
-                    self.epsilon_closure(next, res);
+    pub fn start(&self) -> StateId {
+        self.start
+    }
+
+    pub fn state(&self, state: StateId) -> &State<A> {
+        &self.states[state]
+    }
+
+    pub fn is_accepting_state(&self, state: StateId) -> bool {
+        state == self.end
+    }
+
+    // Thompson's construction leaves a single match state.
+    pub fn accepting_states(&self) -> impl Iterator<Item = StateId> + '_ {
+        std::iter::once(self.end)
+    }
+
+    // Every edge as (from, label, to), in state order. A counted state loops
+    // on its ranges and leaves on them too, or on an epsilon edge when it may
+    // repeat zero times; its bounds are on the state itself.
+    pub fn transitions(&self) -> impl Iterator<Item = (StateId, Label<'_, A>, StateId)> + '_ {
+        self.states.iter().enumerate().flat_map(|(from, state)| {
+            let edges: Vec<(Label<'_, A>, StateId)> = match state {
+                State::Range { ranges, next } => vec![(Label::Ranges(ranges), *next)],
+                State::Counted {
+                    ranges, min, next, ..
+                } => {
+                    let mut edges = vec![
+                        (Label::Ranges(ranges), from),
+                        (Label::Ranges(ranges), *next),
+                    ];
+                    if *min == 0 {
+                        edges.push((Label::Epsilon, *next));
+                    }
+                    edges
+                }
+                State::Split { alternatives } => alternatives
+                    .iter()
+                    .map(|&alternative| (Label::Epsilon, alternative))
+                    .collect(),
+                State::Epsilon { next } => vec![(Label::Epsilon, *next)],
+                State::Match => Vec::new(),
+            };
+            edges.into_iter().map(move |(label, to)| (from, label, to))
+        })
+    }
+
+    // The consuming and match states reachable through epsilon moves, in the
+    // order a backtracking engine would reach them.
+    pub(crate) fn epsilon_closure(&self, state: StateId) -> &[StateId] {
+        &self.epsilon_closures[state]
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn expanded_state_count(&self) -> usize {
+        self.states
+            .iter()
+            .map(|state| match state {
+                State::Counted { min, max, .. } => 1 + count_bound(*min, *max),
+                _ => 1,
+            })
+            .sum()
+    }
+
+    pub fn create_cache(&self) -> Cache {
+        Cache::new(self.states.len())
+    }
+
+    pub(crate) fn start_cache(&self, cache: &mut Cache) {
+        cache.reset(self.states.len());
+        self.enter(self.start, &mut cache.current, &mut cache.current_counts, 0);
+    }
+
+    // Moves the cache over the char at the given char position.
+    //
+    // A counted state keeps the positions at which each of its active
+    // repetitions was entered, oldest first, so its counts are the distance
+    // from the current position and never need to be incremented one by one.
+    pub(crate) fn step_cache(&self, cache: &mut Cache, position: usize, c: A) {
+        let Cache {
+            current,
+            next,
+            current_counts,
+            next_counts,
+        } = cache;
+        for &curr in current.iter() {
+            match &self.states[curr] {
+                State::Range {
+                    ranges,
+                    next: next_state,
+                } if ranges.iter().any(|range| range.contains(&c)) => {
+                    self.enter(*next_state, next, next_counts, position + 1);
+                }
+                State::Counted {
+                    ranges,
+                    min,
+                    max,
+                    next: next_state,
+                } if ranges.iter().any(|range| range.contains(&c)) => {
+                    let entries = &mut current_counts[curr];
+                    match max {
+                        Some(max) => {
+                            while entries
+                                .front()
+                                .is_some_and(|&entry| position + 1 - entry > *max)
+                            {
+                                entries.pop_front();
+                            }
+                        }
+                        None => {
+                            while entries.len() >= 2 && position + 1 - entries[1] >= *min {
+                                entries.pop_front();
+                            }
+                        }
+                    }
+
+                    let Some(&oldest) = entries.front() else {
+                        continue;
+                    };
+
+                    entries.append(&mut next_counts[curr]);
+                    mem::swap(entries, &mut next_counts[curr]);
+                    next.insert(curr);
+
+                    if position + 1 - oldest >= *min {
+                        self.enter(*next_state, next, next_counts, position + 1);
+                    }
                 }
+                _ => {}
             }
         }
+        cache.swap();
     }
 
-    pub fn is_match(&self, input: &str) -> bool {
-        let mut epsilon_closure_start: HashSet<StateId> = HashSet::new();
-        self.epsilon_closure(self.start, &mut epsilon_closure_start);
-
-        let mut current_states: HashSet<StateId> = HashSet::new();
-        current_states.extend(epsilon_closure_start);
-        for c in input.chars() {
-            let mut next_states: HashSet<StateId> = HashSet::new();
-            for &curr in &current_states {
-                if let Some(adj) = self.transitions[curr].get(&Alphabet::Char(c)) {
-                    for &next in adj {
-                        let mut epsilon_closure_next: HashSet<StateId> = HashSet::new();
-                        self.epsilon_closure(next, &mut epsilon_closure_next);
-                        next_states.extend(epsilon_closure_next);
+    pub(crate) fn is_cache_accepting(&self, cache: &Cache) -> bool {
+        cache.current.contains(self.end)
+    }
+
+    fn enter(
+        &self,
+        state: StateId,
+        states: &mut SparseSet,
+        counts: &mut [VecDeque<usize>],
+        position: usize,
+    ) {
+        for &epsilon_state in &self.epsilon_closures[state] {
+            states.insert(epsilon_state);
+            if let State::Counted { .. } = self.states[epsilon_state]
+                && counts[epsilon_state].back() != Some(&position)
+            {
+                counts[epsilon_state].push_back(position);
+            }
+        }
+    }
+
+    pub(crate) fn next_count(&self, state: StateId, count: usize) -> Option<usize> {
+        let State::Counted { min, max, .. } = self.states[state] else {
+            unreachable!("only counted states have a count");
+        };
+        match max {
+            Some(max) => (count < max).then_some(count + 1),
+            None => Some((count + 1).min(count_bound(min, max))),
+        }
+    }
+
+    // Every state keeps its id and becomes a split over its incoming edges,
+    // each turned around to point back at the state it came from.
+    pub fn reverse(&self) -> EpsilonNfa<A> {
+        let mut incoming: Vec<Vec<StateId>> = vec![Vec::new(); self.states.len()];
+        let mut edge_states: Vec<State<A>> = Vec::new();
+        for (curr, state) in self.states.iter().enumerate() {
+            match state {
+                State::Range { ranges, next } => {
+                    incoming[*next].push(self.states.len() + edge_states.len());
+                    edge_states.push(State::Range {
+                        ranges: ranges.clone(),
+                        next: curr,
+                    });
+                }
+                State::Counted {
+                    ranges,
+                    min,
+                    max,
+                    next,
+                } => {
+                    incoming[*next].push(self.states.len() + edge_states.len());
+                    edge_states.push(State::Counted {
+                        ranges: ranges.clone(),
+                        min: *min,
+                        max: *max,
+                        next: curr,
+                    });
+                }
+                State::Split { alternatives } => {
+                    for &alternative in alternatives {
+                        incoming[alternative].push(curr);
                     }
                 }
+                State::Epsilon { next } => incoming[*next].push(curr),
+                State::Match => {}
             }
-            current_states = next_states;
         }
 
-        current_states.contains(&self.end)
+        let end = self.states.len() + edge_states.len();
+        incoming[self.start].push(end);
+
+        let mut states: Vec<State<A>> = incoming
+            .into_iter()
+            .map(|alternatives| State::Split { alternatives })
+            .collect();
+        states.extend(edge_states);
+        states.push(State::Match);
+
+        EpsilonNfa::new(states, self.end, end)
     }
 
-    pub fn to_nfa(&self) -> Nfa {
-        let mut nfa_builder = NfaBuilder::new();
+    pub fn to_nfa(&self) -> Nfa<A> {
+        self.to_nfa_with_limits(&DfaLimits::new())
+            .expect("unlimited NFA construction cannot fail")
+    }
 
-        let n = self.transitions.len();
-        while nfa_builder.add_state() != n {}
+    pub fn to_nfa_with_limits(&self, limits: &DfaLimits) -> Result<Nfa<A>, DfaError> {
+        limits.check_nfa_states(self.expanded_state_count())?;
+
+        let mut nfa_builder = NfaBuilder::default();
+
+        let mut mappings: HashMap<Position, StateId> = HashMap::new();
+        let nfa_start = nfa_builder.add_state();
+        mappings.insert((self.start, 0), nfa_start);
 
-        let nfa_start = self.start;
         let mut nfa_end: HashSet<StateId> = HashSet::new();
 
-        let mut state_epsilon_closure: Vec<HashSet<StateId>> = Vec::new();
-        state_epsilon_closure.resize(n, Default::default());
-        for (curr, curr_epsilon_closure) in state_epsilon_closure.iter_mut().enumerate() {
-            self.epsilon_closure(curr, curr_epsilon_closure);
+        let mut stack: Vec<Position> = Vec::from([(self.start, 0)]);
+        while let Some(position) = stack.pop() {
+            let nfa_curr = mappings[&position];
+
+            let (edges, is_accepting) = self.position_edges(position);
+            if is_accepting {
+                nfa_end.insert(nfa_curr);
+            }
 
-            if curr_epsilon_closure.contains(&self.end) {
-                nfa_end.insert(curr);
+            for (ranges, next_position) in edges {
+                let nfa_next = match mappings.get(&next_position) {
+                    Some(&nfa_next) => nfa_next,
+                    None => {
+                        let nfa_next = nfa_builder.add_state();
+                        mappings.insert(next_position, nfa_next);
+                        stack.push(next_position);
+                        nfa_next
+                    }
+                };
+                for c in ranges.iter().flat_map(range_symbols) {
+                    nfa_builder.transitions[nfa_curr]
+                        .entry(c)
+                        .or_default()
+                        .insert(nfa_next);
+                }
             }
         }
 
-        for curr in 0..n {
-            for &epsilon_state in &state_epsilon_closure[curr] {
-                for (&alphabet, next_states) in &self.transitions[epsilon_state] {
-                    if let Alphabet::Char(c) = alphabet {
-                        for &next in next_states {
-                            nfa_builder.transitions[curr]
-                                .entry(c)
-                                .or_default()
-                                .extend(state_epsilon_closure[next].iter().copied());
-                        }
+        Ok(Nfa::new(nfa_builder.transitions, nfa_start, nfa_end))
+    }
+
+    // The edges leaving a position, where a position is a state together with
+    // the repetitions a counted state has done, and whether it can accept.
+    fn position_edges(&self, position: Position) -> (Vec<PositionEdge<'_, A>>, bool) {
+        let (curr, count) = position;
+        let mut edges: Vec<PositionEdge<'_, A>> = Vec::new();
+        let epsilon_closure: &[StateId] = match &self.states[curr] {
+            State::Counted {
+                ranges, min, next, ..
+            } if count > 0 => {
+                if let Some(next_count) = self.next_count(curr, count) {
+                    edges.push((ranges, (curr, next_count)));
+                }
+                if count >= *min {
+                    &self.epsilon_closures[*next]
+                } else {
+                    &[]
+                }
+            }
+            _ => &self.epsilon_closures[curr],
+        };
+
+        let mut is_accepting = false;
+        for &epsilon_state in epsilon_closure {
+            match &self.states[epsilon_state] {
+                State::Range { ranges, next } => edges.push((ranges, (*next, 0))),
+                State::Counted { ranges, .. } => {
+                    if let Some(next_count) = self.next_count(epsilon_state, 0) {
+                        edges.push((ranges, (epsilon_state, next_count)));
                     }
                 }
+                State::Match => is_accepting = true,
+                State::Split { .. } | State::Epsilon { .. } => {}
+            }
+        }
+        (edges, is_accepting)
+    }
+}
+
+impl EpsilonNfa {
+    pub fn with_match_kind(mut self, match_kind: MatchKind) -> Self {
+        self.match_kind = match_kind;
+        self
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    pub(crate) fn find_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let (match_start, match_end) =
+            pike_vm::find_at(self, haystack, start, self.match_kind, anchors)?;
+        Some(Match::new(haystack, match_start, match_end))
+    }
+
+    pub(crate) fn rfind_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        end: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let reversed = self.reversed.get_or_init(|| Box::new(self.reverse()));
+        rfind_reversed(haystack, end, anchors, |haystack, start, anchors| {
+            reversed.find_anchored_at(haystack, start, anchors)
+        })
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        self.is_match_with_cache(&mut self.create_cache(), input)
+    }
+
+    pub fn is_match_with_cache(&self, cache: &mut Cache, input: &str) -> bool {
+        self.start_cache(cache);
+        for (i, c) in input.chars().enumerate() {
+            self.step_cache(cache, i, c);
+
+            if cache.current.is_empty() {
+                return false;
             }
         }
 
-        Nfa::new(nfa_builder.transitions, nfa_start, nfa_end)
+        self.is_cache_accepting(cache)
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_epsilon_nfa(self)
+    }
+}
+
+impl Searcher for EpsilonNfa {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        self.find_anchored_at(haystack, start, &Anchors::default())
+    }
+}
+
+impl ReverseSearcher for EpsilonNfa {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        self.rfind_anchored_at(haystack, end, &Anchors::default())
+    }
+}
+
+// While reading, the εNFA is in a set of positions: states together with the
+// repetitions done by the counted ones.
+impl<A: Symbol> Automaton for EpsilonNfa<A> {
+    type Symbol = A;
+    type Label<'a>
+        = Label<'a, A>
+    where
+        A: 'a;
+    type States = BTreeSet<Position>;
+
+    fn state_count(&self) -> usize {
+        EpsilonNfa::state_count(self)
+    }
+
+    fn start(&self) -> StateId {
+        EpsilonNfa::start(self)
+    }
+
+    fn is_accepting_state(&self, state: StateId) -> bool {
+        EpsilonNfa::is_accepting_state(self, state)
+    }
+
+    fn transitions(&self) -> impl Iterator<Item = (StateId, Label<'_, A>, StateId)> + '_ {
+        EpsilonNfa::transitions(self)
+    }
+
+    fn start_states(&self) -> BTreeSet<Position> {
+        BTreeSet::from([(self.start, 0)])
+    }
+
+    fn step(&self, states: &mut BTreeSet<Position>, c: A) {
+        *states = states
+            .iter()
+            .flat_map(|&position| self.position_edges(position).0)
+            .filter(|(ranges, _)| ranges.iter().any(|range| range.contains(&c)))
+            .map(|(_, next_position)| next_position)
+            .collect();
+    }
+
+    fn is_accepting(&self, states: &BTreeSet<Position>) -> bool {
+        states
+            .iter()
+            .any(|&position| self.position_edges(position).1)
+    }
+}
+
+// Counts above min are indistinguishable for an unbounded repetition, so they
+// saturate at min (or one, to keep a looping count for {0,}).
+fn count_bound(min: usize, max: Option<usize>) -> usize {
+    match max {
+        Some(max) => max,
+        None => min.max(1),
     }
 }
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+use crate::{
+    StateId, ast::Ast, error::Error, minimized_dfa::MinimizedDfa, parser::Parser, symbol::Symbol,
+};
+
+// How two languages compare, with a witness when they do not compare as asked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Comparison<W = String> {
+    Holds,
+    // A shortest string telling the languages apart, the smallest of those if
+    // there are several.
+    Counterexample(W),
+}
+
+impl<W> Comparison<W> {
+    pub fn holds(&self) -> bool {
+        matches!(self, Comparison::Holds)
+    }
+
+    fn map<V>(self, f: impl FnOnce(W) -> V) -> Comparison<V> {
+        match self {
+            Comparison::Holds => Comparison::Holds,
+            Comparison::Counterexample(witness) => Comparison::Counterexample(f(witness)),
+        }
+    }
+}
+
+impl<W> From<Option<W>> for Comparison<W> {
+    fn from(counterexample: Option<W>) -> Self {
+        match counterexample {
+            Some(witness) => Comparison::Counterexample(witness),
+            None => Comparison::Holds,
+        }
+    }
+}
+
+// Whether both automata accept the same strings. If not, the counterexample is
+// a string only one of them accepts.
+pub fn equivalent<A: Symbol>(
+    left: &MinimizedDfa<A>,
+    right: &MinimizedDfa<A>,
+) -> Comparison<Vec<A>> {
+    shortest_accepted(&left.symmetric_difference(right)).into()
+}
+
+// Whether every string `left` accepts is accepted by `right` too. If not, the
+// counterexample is a string only `left` accepts.
+pub fn is_subset<A: Symbol>(left: &MinimizedDfa<A>, right: &MinimizedDfa<A>) -> Comparison<Vec<A>> {
+    shortest_accepted(&left.difference(right)).into()
+}
+
+// Like equivalent, for the whole strings two patterns match.
+pub fn equivalent_patterns(left: &str, right: &str) -> Result<Comparison, Error> {
+    let comparison = equivalent(&compile(left)?, &compile(right)?);
+    Ok(comparison.map(String::from_iter))
+}
+
+// Like is_subset, for the whole strings two patterns match.
+pub fn is_subset_patterns(left: &str, right: &str) -> Result<Comparison, Error> {
+    let comparison = is_subset(&compile(left)?, &compile(right)?);
+    Ok(comparison.map(String::from_iter))
+}
+
+// Whole strings are compared, where the `^` and `$` anchoring a pattern always
+// hold, so they are left out.
+fn compile(pattern: &str) -> Result<MinimizedDfa, Error> {
+    let (ast, _): (Ast, _) = Parser::new(pattern)
+        .parse_anchored()
+        .map_err(Error::Syntax)?;
+    let epsilon_nfa = ast.to_epsilon_nfa().map_err(Error::Compile)?;
+    Ok(epsilon_nfa.to_nfa().to_dfa().to_minimized_dfa())
+}
+
+// Breadth first with the symbols in order, so the first accepting state
+// reached is reached by the smallest of the shortest strings. A default edge
+// is only ever taken on its smallest symbol.
+fn shortest_accepted<A: Symbol>(minimized_dfa: &MinimizedDfa<A>) -> Option<Vec<A>> {
+    let start = minimized_dfa.start();
+    let mut parent: Vec<Option<(StateId, A)>> = vec![None; minimized_dfa.state_count()];
+    let mut visited: Vec<bool> = vec![false; minimized_dfa.state_count()];
+    visited[start] = true;
+
+    let mut queue: VecDeque<StateId> = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        if minimized_dfa.is_accepting_state(state) {
+            let mut input: Vec<A> = Vec::new();
+            let mut curr = state;
+            while let Some((prev, c)) = parent[curr] {
+                input.push(c);
+                curr = prev;
+            }
+            input.reverse();
+            return Some(input);
+        }
+        for (c, next) in minimized_dfa.first_edges(state) {
+            if !visited[next] {
+                visited[next] = true;
+                parent[next] = Some((state, c));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::{
+    Alphabet, StateId, engine::EngineKind, limits::DfaError, search::MatchKind, symbol::Symbol,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    // The pattern is not a valid regular expression.
+    Syntax(String),
+    // Building the NFA or DFA the pattern was compiled to outgrew its limits
+    // or was stopped.
+    Dfa(DfaError),
+    // The engine the pattern was forced to cannot report that kind of match.
+    MatchKind(MatchKindError),
+    // The pattern is valid but no engine could be built for it.
+    Compile(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax(message) => write!(f, "syntax error: {message}"),
+            Error::Dfa(error) => write!(f, "compile error: {error}"),
+            Error::MatchKind(error) => write!(f, "compile error: {error}"),
+            Error::Compile(message) => write!(f, "compile error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Dfa(error) => Some(error),
+            Error::MatchKind(error) => Some(error),
+            Error::Syntax(_) | Error::Compile(_) => None,
+        }
+    }
+}
+
+impl From<DfaError> for Error {
+    fn from(error: DfaError) -> Self {
+        Error::Dfa(error)
+    }
+}
+
+impl From<MatchKindError> for Error {
+    fn from(error: MatchKindError) -> Self {
+        Error::MatchKind(error)
+    }
+}
+
+// An engine was asked for a match kind it cannot report. Only the εNFA and
+// the literal engines keep the order of the alternatives that leftmost-first
+// needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchKindError {
+    pub engine: EngineKind,
+    pub match_kind: MatchKind,
+}
+
+impl fmt::Display for MatchKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the {:?} engine cannot report {:?} matches",
+            self.engine, self.match_kind
+        )
+    }
+}
+
+impl std::error::Error for MatchKindError {}
+
+// An automaton put together by hand does not hold together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolBuildError<A> {
+    // The id was not handed out by the builder.
+    UnknownState(StateId),
+    // No start state was set.
+    MissingStart,
+    // A DFA state was given two targets for the same symbol.
+    Nondeterministic { state: StateId, symbol: A },
+}
+
+impl<A: Symbol> fmt::Display for SymbolBuildError<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolBuildError::UnknownState(state) => write!(f, "state {state} does not exist"),
+            SymbolBuildError::MissingStart => write!(f, "no start state was set"),
+            SymbolBuildError::Nondeterministic { state, symbol } => write!(
+                f,
+                "state {state} already has a different transition on {symbol:?}"
+            ),
+        }
+    }
+}
+
+impl<A: Symbol> std::error::Error for SymbolBuildError<A> {}
+
+// What the builders of automata over chars report.
+pub type BuildError = SymbolBuildError<Alphabet>;
@@ -0,0 +1,410 @@
+use std::{ops::RangeInclusive, sync::OnceLock};
+
+use crate::{
+    ast::Ast,
+    engine::EngineKind,
+    error::MatchKindError,
+    prefilter::Prefilter,
+    search::{
+        Anchors, Match, MatchKind, ReverseSearcher, Searcher, Threads, find_leftmost,
+        rfind_reversed,
+    },
+    stream::StreamMatcher,
+};
+
+// Bit 0 stands for the initial state, leaving the other bits of a u64 for the
+// positions of the pattern.
+pub(crate) const MAX_GLUSHKOV_POSITIONS: usize = u64::BITS as usize - 1;
+
+pub(crate) const INITIAL: u64 = 1;
+
+#[derive(Debug, Clone, Copy)]
+struct Linearized {
+    nullable: bool,
+    first: u64,
+    last: u64,
+}
+
+const EMPTY: Linearized = Linearized {
+    nullable: true,
+    first: 0,
+    last: 0,
+};
+
+pub(crate) struct GlushkovBuilder {
+    classes: Vec<Vec<RangeInclusive<char>>>,
+    follow: Vec<u64>,
+}
+
+impl GlushkovBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            classes: Vec::new(),
+            follow: vec![0],
+        }
+    }
+
+    pub(crate) fn build(mut self, ast: &Ast, prefilter: Option<Prefilter>) -> Glushkov {
+        let linearized = self.linearize(ast);
+        self.follow[0] = linearized.first;
+        let accepting = linearized.last | if linearized.nullable { INITIAL } else { 0 };
+
+        let mut ascii_masks = Box::new([0; 128]);
+        for (c, mask) in ascii_masks.iter_mut().enumerate() {
+            *mask = self.mask_of(c as u32);
+        }
+
+        // The non-ASCII parts of the classes are cut into disjoint intervals
+        // that each share a single mask.
+        let mut boundaries: Vec<u32> = Vec::new();
+        for ranges in &self.classes {
+            for range in ranges {
+                let (start, end) = (*range.start() as u32, *range.end() as u32);
+                if end >= 128 {
+                    boundaries.push(start.max(128));
+                    boundaries.push(end + 1);
+                }
+            }
+        }
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        let intervals: Vec<(u32, u32, u64)> = boundaries
+            .windows(2)
+            .map(|window| (window[0], window[1] - 1, self.mask_of(window[0])))
+            .filter(|&(_, _, mask)| mask != 0)
+            .collect();
+
+        Glushkov {
+            position_count: self.classes.len(),
+            follow_tables: follow_tables(&self.follow),
+            ascii_masks,
+            intervals,
+            accepting,
+            prefilter,
+            match_kind: MatchKind::default(),
+            reversed: OnceLock::new(),
+        }
+    }
+
+    fn mask_of(&self, c: u32) -> u64 {
+        self.classes
+            .iter()
+            .enumerate()
+            .filter(|(_, ranges)| {
+                ranges
+                    .iter()
+                    .any(|range| (*range.start() as u32..=*range.end() as u32).contains(&c))
+            })
+            .fold(0, |mask, (position, _)| mask | 1 << (position + 1))
+    }
+
+    fn linearize(&mut self, ast: &Ast) -> Linearized {
+        match ast {
+            Ast::Empty => EMPTY,
+            Ast::Class(ranges) => {
+                self.classes.push(ranges.clone());
+                self.follow.push(0);
+                let bit = 1 << self.classes.len();
+                Linearized {
+                    nullable: false,
+                    first: bit,
+                    last: bit,
+                }
+            }
+            Ast::Concat(items) => items.iter().fold(EMPTY, |concat_res, item| {
+                let item_res = self.linearize(item);
+                self.concat(concat_res, item_res)
+            }),
+            Ast::Alternation(alternatives) => alternatives.iter().fold(
+                Linearized {
+                    nullable: false,
+                    first: 0,
+                    last: 0,
+                },
+                |union_res, alternative| {
+                    let alternative_res = self.linearize(alternative);
+                    Linearized {
+                        nullable: union_res.nullable || alternative_res.nullable,
+                        first: union_res.first | alternative_res.first,
+                        last: union_res.last | alternative_res.last,
+                    }
+                },
+            ),
+            Ast::Repeat { inner, min, max } => match (*min, *max) {
+                (0, None) => {
+                    let inner_res = self.linearize(inner);
+                    self.star(inner_res)
+                }
+                (1, None) => {
+                    let inner_res = self.linearize(inner);
+                    self.plus(inner_res)
+                }
+                (0, Some(1)) => {
+                    let inner_res = self.linearize(inner);
+                    question(inner_res)
+                }
+                (min, max) => {
+                    let mut repeated_res = EMPTY;
+                    for _ in 0..min {
+                        let inner_res = self.linearize(inner);
+                        repeated_res = self.concat(repeated_res, inner_res);
+                    }
+
+                    let optional_res = match max {
+                        Some(max) => {
+                            let mut optional_res = EMPTY;
+                            for _ in min..max {
+                                let inner_res = self.linearize(inner);
+                                optional_res = question(self.concat(inner_res, optional_res));
+                            }
+                            optional_res
+                        }
+                        None => {
+                            let inner_res = self.linearize(inner);
+                            self.star(inner_res)
+                        }
+                    };
+                    self.concat(repeated_res, optional_res)
+                }
+            },
+            Ast::Capture { inner, .. } => self.linearize(inner),
+        }
+    }
+
+    fn concat(&mut self, left: Linearized, right: Linearized) -> Linearized {
+        for position in bits(left.last) {
+            self.follow[position] |= right.first;
+        }
+
+        Linearized {
+            nullable: left.nullable && right.nullable,
+            first: left.first | if left.nullable { right.first } else { 0 },
+            last: right.last | if right.nullable { left.last } else { 0 },
+        }
+    }
+
+    fn star(&mut self, inner: Linearized) -> Linearized {
+        Linearized {
+            nullable: true,
+            ..self.plus(inner)
+        }
+    }
+
+    fn plus(&mut self, inner: Linearized) -> Linearized {
+        for position in bits(inner.last) {
+            self.follow[position] |= inner.first;
+        }
+        inner
+    }
+}
+
+fn question(inner: Linearized) -> Linearized {
+    Linearized {
+        nullable: true,
+        ..inner
+    }
+}
+
+// follow[p] holds the positions following position p, with position 0 standing
+// for the initial state.
+fn follow_tables(follow: &[u64]) -> Box<[[u64; 256]; 8]> {
+    let mut follow_tables = Box::new([[0; 256]; 8]);
+    for (k, table) in follow_tables.iter_mut().enumerate() {
+        for (byte, next) in table.iter_mut().enumerate() {
+            *next = bits(byte as u64)
+                .filter_map(|bit| follow.get(8 * k + bit))
+                .fold(0, |next, &follow| next | follow);
+        }
+    }
+    follow_tables
+}
+
+fn bits(mut word: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let bit = word.trailing_zeros() as usize;
+        word &= word - 1;
+        Some(bit)
+    })
+}
+
+// A bit-parallel simulation of the Glushkov position automaton: every active
+// position is a bit of a u64, so a step costs a handful of table lookups no
+// matter how many positions are active.
+#[derive(Debug, Clone)]
+pub struct Glushkov {
+    position_count: usize,
+    // follow_tables[k][byte] holds the positions following any of the eight
+    // positions 8k..8k+8 whose bits are set in byte.
+    follow_tables: Box<[[u64; 256]; 8]>,
+    ascii_masks: Box<[u64; 128]>,
+    intervals: Vec<(u32, u32, u64)>,
+    accepting: u64,
+    prefilter: Option<Prefilter>,
+    match_kind: MatchKind,
+    reversed: OnceLock<Box<Glushkov>>,
+}
+
+impl Glushkov {
+    pub fn position_count(&self) -> usize {
+        self.position_count
+    }
+
+    pub fn prefilter(&self) -> Option<&Prefilter> {
+        self.prefilter.as_ref()
+    }
+
+    // The active positions do not record which alternative they came from, so
+    // leftmost-first cannot be told apart from the other matches.
+    pub fn with_match_kind(mut self, match_kind: MatchKind) -> Result<Self, MatchKindError> {
+        if match_kind == MatchKind::LeftmostFirst {
+            return Err(MatchKindError {
+                engine: EngineKind::Glushkov,
+                match_kind,
+            });
+        }
+        self.match_kind = match_kind;
+        Ok(self)
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    // The positions of the reversed pattern are the same, with every follow
+    // edge turned around and the first and last positions trading places.
+    pub fn reverse(&self) -> Glushkov {
+        let follow_of = |position: usize| self.follow_tables[position / 8][1 << (position % 8)];
+        let mut follow = vec![0; self.position_count + 1];
+        follow[0] = self.accepting & !INITIAL;
+        for position in 1..=self.position_count {
+            for next in bits(follow_of(position)) {
+                follow[next] |= 1 << position;
+            }
+        }
+
+        Glushkov {
+            position_count: self.position_count,
+            follow_tables: follow_tables(&follow),
+            ascii_masks: self.ascii_masks.clone(),
+            intervals: self.intervals.clone(),
+            accepting: follow_of(0) | (self.accepting & INITIAL),
+            prefilter: None,
+            match_kind: self.match_kind,
+            reversed: OnceLock::new(),
+        }
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        let mut active = INITIAL;
+        for c in input.chars() {
+            active = self.step(active, c);
+            if active == 0 {
+                return false;
+            }
+        }
+
+        self.is_accepting(active)
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_glushkov(self)
+    }
+
+    pub(crate) fn is_accepting(&self, active: u64) -> bool {
+        active & self.accepting != 0
+    }
+
+    pub(crate) fn step(&self, active: u64, c: char) -> u64 {
+        let follow = active
+            .to_le_bytes()
+            .iter()
+            .zip(self.follow_tables.iter())
+            .fold(0, |follow, (&byte, table)| follow | table[byte as usize]);
+        follow & self.mask(c)
+    }
+
+    fn mask(&self, c: char) -> u64 {
+        let c = c as u32;
+        if c < 128 {
+            return self.ascii_masks[c as usize];
+        }
+
+        let index = self.intervals.partition_point(|&(_, end, _)| end < c);
+        match self.intervals.get(index) {
+            Some(&(start, _, mask)) if start <= c => mask,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn find_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        find_leftmost(
+            self,
+            haystack,
+            start,
+            self.prefilter.as_ref(),
+            self.match_kind,
+            anchors,
+        )
+    }
+
+    pub(crate) fn rfind_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        end: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let reversed = self.reversed.get_or_init(|| Box::new(self.reverse()));
+        rfind_reversed(haystack, end, anchors, |haystack, start, anchors| {
+            reversed.find_anchored_at(haystack, start, anchors)
+        })
+    }
+}
+
+// A thread holds the positions it is at as a mask, so holding one takes
+// whatever positions earlier threads have out of it.
+impl Threads for Glushkov {
+    type State = u64;
+    type Held = u64;
+
+    fn start_state(&self) -> u64 {
+        INITIAL
+    }
+
+    fn next_states(&self, active: u64, c: char, mut push: impl FnMut(u64)) {
+        push(self.step(active, c));
+    }
+
+    fn hold(&self, held: &mut u64, active: u64) -> Option<u64> {
+        let active = active & !*held;
+        *held |= active;
+        (active != 0).then_some(active)
+    }
+
+    fn release(&self, held: &mut u64) {
+        *held = 0;
+    }
+
+    fn thread_accepts(&self, active: u64) -> bool {
+        active & self.accepting != 0
+    }
+}
+
+impl Searcher for Glushkov {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        self.find_anchored_at(haystack, start, &Anchors::default())
+    }
+}
+
+impl ReverseSearcher for Glushkov {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        self.rfind_anchored_at(haystack, end, &Anchors::default())
+    }
+}
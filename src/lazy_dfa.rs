@@ -0,0 +1,443 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::{
+    Alphabet, StateId,
+    engine::EngineKind,
+    error::MatchKindError,
+    nfa::Nfa,
+    prefilter::Prefilter,
+    search::{
+        Anchors, Input, Leftmost, Match, MatchKind, ReverseSearcher, Searcher, Threads,
+        find_leftmost_in, into_ok, rfind_reversed, try_find_leftmost_in,
+    },
+    sparse_set::Cache,
+    state_set::StateSet,
+    stream::StreamMatcher,
+};
+
+const DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+// A search gives up on the cache and falls back to simulating the NFA once the
+// cache has been cleared MIN_CACHE_CLEARS times and the states built since the
+// last clear were each used for fewer than MIN_CHARS_PER_STATE characters.
+const MIN_CACHE_CLEARS: usize = 3;
+const MIN_CHARS_PER_STATE: usize = 10;
+
+static NEXT_LAZY_DFA_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+struct LazyDfaState {
+    subset: StateSet,
+    is_end: bool,
+    transitions: HashMap<Alphabet, StateId>,
+}
+
+// The DFA states built so far, which only make sense to the lazy DFA that
+// created the cache, so searches with any other lazy DFA panic. It also keeps
+// the NFA simulation's cache for when a search falls back to it.
+#[derive(Debug, Clone)]
+pub struct LazyDfaCache {
+    owner: usize,
+    states: Vec<LazyDfaState>,
+    subset_to_state: HashMap<StateSet, StateId>,
+    // The slots of the states a search let go of, handed out again first.
+    free: Vec<StateId>,
+    nfa_cache: Cache,
+}
+
+impl LazyDfaCache {
+    fn new(owner: usize, nfa: &Nfa) -> Self {
+        Self {
+            owner,
+            states: Vec::new(),
+            subset_to_state: HashMap::new(),
+            free: Vec::new(),
+            nfa_cache: nfa.create_cache(),
+        }
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len() - self.free.len()
+    }
+
+    fn clear(&mut self) {
+        self.states.clear();
+        self.subset_to_state.clear();
+        self.free.clear();
+    }
+
+    // Forgets every state but the ones in `keep`, which keep their ids, along
+    // with all the transitions.
+    fn retain(&mut self, keep: &HashSet<StateId>) {
+        self.subset_to_state.clear();
+        self.free.clear();
+        for (state, lazy_state) in self.states.iter_mut().enumerate() {
+            lazy_state.transitions.clear();
+            if keep.contains(&state) {
+                self.subset_to_state
+                    .insert(lazy_state.subset.clone(), state);
+            } else {
+                lazy_state.subset = StateSet::new(0);
+                self.free.push(state);
+            }
+        }
+    }
+
+    fn add_state(&mut self, nfa: &Nfa, subset: StateSet) -> StateId {
+        if let Some(&state) = self.subset_to_state.get(&subset) {
+            return state;
+        }
+
+        let lazy_state = LazyDfaState {
+            is_end: nfa.contains_end(subset.iter()),
+            subset: subset.clone(),
+            transitions: HashMap::new(),
+        };
+        let state = match self.free.pop() {
+            Some(state) => {
+                self.states[state] = lazy_state;
+                state
+            }
+            None => {
+                self.states.push(lazy_state);
+                self.states.len() - 1
+            }
+        };
+        self.subset_to_state.insert(subset, state);
+        state
+    }
+
+    fn next_state(&mut self, nfa: &Nfa, state: StateId, c: Alphabet) -> StateId {
+        if let Some(&next) = self.states[state].transitions.get(&c) {
+            return next;
+        }
+        let next = self.add_state(nfa, nfa.next_states(&self.states[state].subset, c));
+        self.states[state].transitions.insert(c, next);
+        next
+    }
+}
+
+// The caches of the searches that finished, each picked up by a later search.
+#[derive(Debug, Default)]
+struct CachePool(Mutex<Vec<LazyDfaCache>>);
+
+impl Clone for CachePool {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// Searches given a cache of their own build their states in it. All others
+// take a cache from the pool, or create one when the pool is empty, and put it
+// back once done, so later searches pick up the states built so far. Clones
+// start out with an empty pool.
+#[derive(Debug, Clone)]
+pub struct LazyDfa {
+    id: usize,
+    nfa: Nfa,
+    cache_capacity: usize,
+    match_kind: MatchKind,
+    prefilter: Option<Prefilter>,
+    pool: CachePool,
+    reversed: OnceLock<Box<LazyDfa>>,
+}
+
+impl LazyDfa {
+    pub fn new(nfa: Nfa) -> Self {
+        Self::with_cache_capacity(nfa, DEFAULT_CACHE_CAPACITY)
+    }
+
+    pub fn with_cache_capacity(nfa: Nfa, cache_capacity: usize) -> Self {
+        Self {
+            id: NEXT_LAZY_DFA_ID.fetch_add(1, Ordering::Relaxed),
+            nfa,
+            cache_capacity: cache_capacity.max(1),
+            match_kind: MatchKind::default(),
+            prefilter: None,
+            pool: CachePool::default(),
+            reversed: OnceLock::new(),
+        }
+    }
+
+    pub fn with_prefilter(mut self, prefilter: Option<Prefilter>) -> Self {
+        self.prefilter = prefilter;
+        self
+    }
+
+    pub fn prefilter(&self) -> Option<&Prefilter> {
+        self.prefilter.as_ref()
+    }
+
+    pub fn with_match_kind(mut self, match_kind: MatchKind) -> Result<Self, MatchKindError> {
+        if match_kind == MatchKind::LeftmostFirst {
+            return Err(MatchKindError {
+                engine: EngineKind::LazyDfa,
+                match_kind,
+            });
+        }
+        self.match_kind = match_kind;
+        Ok(self)
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    pub fn reverse(&self) -> LazyDfa {
+        LazyDfa {
+            match_kind: self.match_kind,
+            ..LazyDfa::with_cache_capacity(self.nfa.reverse(), self.cache_capacity)
+        }
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_nfa(&self.nfa)
+    }
+
+    fn start_subset(&self) -> StateSet {
+        let mut start = StateSet::new(self.nfa.state_count());
+        start.insert(self.nfa.start());
+        start
+    }
+
+    fn with_pooled_cache<R>(&self, search: impl FnOnce(&mut LazyDfaCache) -> R) -> R {
+        let pooled = self.pool.0.lock().ok().and_then(|mut pool| pool.pop());
+        let mut cache = pooled.unwrap_or_else(|| self.create_cache());
+        let found = search(&mut cache);
+        if let Ok(mut pool) = self.pool.0.lock() {
+            pool.push(cache);
+        }
+        found
+    }
+
+    pub(crate) fn find_in<I: Input + ?Sized>(
+        &self,
+        input: &mut I,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Result<Option<(usize, usize)>, I::Error> {
+        self.with_pooled_cache(|cache| self.find_in_with_cache(cache, input, start, anchors))
+    }
+
+    // Falls back to simulating the NFA from the first thread on once the cache
+    // thrashes, the same way is_match_with_cache does.
+    fn find_in_with_cache<I: Input + ?Sized>(
+        &self,
+        cache: &mut LazyDfaCache,
+        input: &mut I,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Result<Option<(usize, usize)>, I::Error> {
+        self.check_owner(cache);
+        let start_state = cache.add_state(&self.nfa, self.start_subset());
+        let threads = LazyDfaThreads {
+            lazy_dfa: self,
+            cache: RefCell::new(cache),
+            start: start_state,
+            cache_clears: Cell::new(0),
+            chars_since_clear: Cell::new(0),
+            gave_up: Cell::new(false),
+        };
+        let found = try_find_leftmost_in(
+            &threads,
+            input,
+            start,
+            self.prefilter.as_ref(),
+            self.match_kind,
+            anchors,
+        )?;
+        match found {
+            Leftmost::Done(found) => Ok(found),
+            Leftmost::GaveUp(start) => find_leftmost_in(
+                &self.nfa,
+                input,
+                start,
+                self.prefilter.as_ref(),
+                self.match_kind,
+                anchors,
+            ),
+        }
+    }
+
+    pub fn find_at_with_cache<'h>(
+        &self,
+        cache: &mut LazyDfaCache,
+        haystack: &'h str,
+        start: usize,
+    ) -> Option<Match<'h>> {
+        let found =
+            into_ok(self.find_in_with_cache(cache, &mut { haystack }, start, &Anchors::default()));
+        found.map(|(start, end)| Match::new(haystack, start, end))
+    }
+
+    pub(crate) fn find_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let found = into_ok(self.find_in(&mut { haystack }, start, anchors));
+        found.map(|(start, end)| Match::new(haystack, start, end))
+    }
+
+    pub(crate) fn rfind_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        end: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let reversed = self.reversed.get_or_init(|| Box::new(self.reverse()));
+        rfind_reversed(haystack, end, anchors, |haystack, start, anchors| {
+            reversed.find_anchored_at(haystack, start, anchors)
+        })
+    }
+
+    pub fn create_cache(&self) -> LazyDfaCache {
+        LazyDfaCache::new(self.id, &self.nfa)
+    }
+
+    fn check_owner(&self, cache: &LazyDfaCache) {
+        assert_eq!(
+            cache.owner, self.id,
+            "the lazy DFA cache was created by another lazy DFA"
+        );
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        self.with_pooled_cache(|cache| self.is_match_with_cache(cache, input))
+    }
+
+    // Keeps the states built during the search in the cache, so later searches
+    // with the same cache can pick up where this one left off.
+    pub fn is_match_with_cache(&self, cache: &mut LazyDfaCache, input: &str) -> bool {
+        self.check_owner(cache);
+        let mut curr = cache.add_state(&self.nfa, self.start_subset());
+        let mut cache_clears = 0;
+        let mut chars_since_clear = 0;
+        for (i, c) in input.char_indices() {
+            if cache.states[curr].subset.is_empty() {
+                return false;
+            }
+
+            curr = match cache.states[curr].transitions.get(&c) {
+                Some(&next) => next,
+                None => {
+                    let next_subset = self.nfa.next_states(&cache.states[curr].subset, c);
+                    match cache.subset_to_state.get(&next_subset) {
+                        Some(&next) => {
+                            cache.states[curr].transitions.insert(c, next);
+                            next
+                        }
+                        None if cache.state_count() >= self.cache_capacity => {
+                            if cache_clears >= MIN_CACHE_CLEARS
+                                && chars_since_clear < MIN_CHARS_PER_STATE * cache.state_count()
+                            {
+                                cache.clear();
+                                return self.nfa.is_match_from(
+                                    &mut cache.nfa_cache,
+                                    next_subset.iter(),
+                                    &input[i + c.len_utf8()..],
+                                );
+                            }
+
+                            cache.clear();
+                            cache_clears += 1;
+                            chars_since_clear = 0;
+                            cache.add_state(&self.nfa, next_subset)
+                        }
+                        None => {
+                            let next = cache.add_state(&self.nfa, next_subset);
+                            cache.states[curr].transitions.insert(c, next);
+                            next
+                        }
+                    }
+                }
+            };
+            chars_since_clear += 1;
+        }
+
+        cache.states[curr].is_end
+    }
+}
+
+// Each thread holds a state of the lazy DFA. Once the cache is full, the
+// states no thread holds are let go of between two steps, so the ones in use
+// stay put.
+struct LazyDfaThreads<'d, 'c> {
+    lazy_dfa: &'d LazyDfa,
+    cache: RefCell<&'c mut LazyDfaCache>,
+    start: StateId,
+    cache_clears: Cell<usize>,
+    chars_since_clear: Cell<usize>,
+    gave_up: Cell<bool>,
+}
+
+impl Threads for LazyDfaThreads<'_, '_> {
+    type State = StateId;
+    type Held = HashSet<StateId>;
+
+    fn start_state(&self) -> StateId {
+        self.start
+    }
+
+    fn next_states(&self, state: StateId, c: char, mut push: impl FnMut(StateId)) {
+        let mut cache = self.cache.borrow_mut();
+        let next = cache.next_state(&self.lazy_dfa.nfa, state, c);
+        let is_dead = cache.states[next].subset.is_empty();
+        drop(cache);
+        if !is_dead {
+            push(next);
+        }
+    }
+
+    fn hold(&self, held: &mut HashSet<StateId>, state: StateId) -> Option<StateId> {
+        held.insert(state).then_some(state)
+    }
+
+    fn release(&self, held: &mut HashSet<StateId>) {
+        let mut cache = self.cache.borrow_mut();
+        let chars_since_clear = self.chars_since_clear.get() + 1;
+        self.chars_since_clear.set(chars_since_clear);
+        if cache.state_count() >= self.lazy_dfa.cache_capacity {
+            if self.cache_clears.get() >= MIN_CACHE_CLEARS
+                && chars_since_clear < MIN_CHARS_PER_STATE * cache.state_count()
+            {
+                cache.clear();
+                self.gave_up.set(true);
+            } else {
+                held.insert(self.start);
+                cache.retain(held);
+                self.cache_clears.set(self.cache_clears.get() + 1);
+                self.chars_since_clear.set(0);
+            }
+        }
+        held.clear();
+    }
+
+    fn gives_up(&self) -> bool {
+        self.gave_up.get()
+    }
+
+    fn thread_accepts(&self, state: StateId) -> bool {
+        self.cache.borrow().states[state].is_end
+    }
+}
+
+impl Searcher for LazyDfa {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        self.find_anchored_at(haystack, start, &Anchors::default())
+    }
+}
+
+impl ReverseSearcher for LazyDfa {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        self.rfind_anchored_at(haystack, end, &Anchors::default())
+    }
+}
@@ -1,10 +1,31 @@
 pub mod regex;
 
+mod accel;
+mod aho_corasick;
+mod ast;
+mod automaton;
 mod dfa;
+mod engine;
 mod epsilon_nfa;
+mod equivalence;
+mod error;
+mod glushkov;
+mod lazy_dfa;
+mod limits;
+mod literal;
 mod minimized_dfa;
 mod nfa;
+mod otherwise;
 mod parser;
+mod pattern;
+mod pike_vm;
+mod prefilter;
+mod reader;
+mod search;
+mod sparse_set;
+mod state_set;
+mod stream;
+mod symbol;
 
 type Alphabet = char;
 type StateId = usize;
@@ -0,0 +1,139 @@
+use std::{fmt, sync::Arc, time::Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DfaError {
+    TooManyNfaStates { estimated: usize, limit: usize },
+    TooManyStates { limit: usize },
+    TooManyTransitions { limit: usize },
+    DeadlineExceeded,
+    Cancelled,
+}
+
+impl fmt::Display for DfaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DfaError::TooManyNfaStates { estimated, limit } => write!(
+                f,
+                "NFA would need about {estimated} states, exceeding the limit of {limit}"
+            ),
+            DfaError::TooManyStates { limit } => {
+                write!(f, "DFA exceeds the limit of {limit} states")
+            }
+            DfaError::TooManyTransitions { limit } => {
+                write!(f, "DFA exceeds the limit of {limit} transitions")
+            }
+            DfaError::DeadlineExceeded => write!(f, "DFA construction exceeded its deadline"),
+            DfaError::Cancelled => write!(f, "DFA construction was cancelled"),
+        }
+    }
+}
+
+impl DfaError {
+    // The automaton outgrew a budget, as opposed to its construction being
+    // stopped from outside.
+    pub fn is_size_limit(&self) -> bool {
+        matches!(
+            self,
+            DfaError::TooManyNfaStates { .. }
+                | DfaError::TooManyStates { .. }
+                | DfaError::TooManyTransitions { .. }
+        )
+    }
+}
+
+impl std::error::Error for DfaError {}
+
+type CancelCallback = Arc<dyn Fn() -> bool + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct DfaLimits {
+    max_nfa_states: Option<usize>,
+    max_states: Option<usize>,
+    max_transitions: Option<usize>,
+    deadline: Option<Instant>,
+    cancel: Option<CancelCallback>,
+}
+
+impl DfaLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_nfa_states(mut self, max_nfa_states: usize) -> Self {
+        self.max_nfa_states = Some(max_nfa_states);
+        self
+    }
+
+    pub fn max_states(mut self, max_states: usize) -> Self {
+        self.max_states = Some(max_states);
+        self
+    }
+
+    pub fn max_transitions(mut self, max_transitions: usize) -> Self {
+        self.max_transitions = Some(max_transitions);
+        self
+    }
+
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn cancel_when<F: Fn() -> bool + Send + Sync + 'static>(mut self, cancel: F) -> Self {
+        self.cancel = Some(Arc::new(cancel));
+        self
+    }
+
+    pub(crate) fn check_nfa_states(&self, estimated: usize) -> Result<(), DfaError> {
+        match self.max_nfa_states {
+            Some(limit) if estimated > limit => {
+                Err(DfaError::TooManyNfaStates { estimated, limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_states(&self, states: usize) -> Result<(), DfaError> {
+        match self.max_states {
+            Some(limit) if states > limit => Err(DfaError::TooManyStates { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_transitions(&self, transitions: usize) -> Result<(), DfaError> {
+        match self.max_transitions {
+            Some(limit) if transitions > limit => Err(DfaError::TooManyTransitions { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    // The deadline and the cancel callback cost more to ask, so they are only
+    // checked once per expanded state.
+    pub(crate) fn check_interrupted(&self) -> Result<(), DfaError> {
+        if let Some(deadline) = self.deadline
+            && Instant::now() >= deadline
+        {
+            return Err(DfaError::DeadlineExceeded);
+        }
+
+        if let Some(cancel) = &self.cancel
+            && cancel()
+        {
+            return Err(DfaError::Cancelled);
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for DfaLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DfaLimits")
+            .field("max_nfa_states", &self.max_nfa_states)
+            .field("max_states", &self.max_states)
+            .field("max_transitions", &self.max_transitions)
+            .field("deadline", &self.deadline)
+            .field("cancel", &self.cancel.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
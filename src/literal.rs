@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+
+use crate::{
+    search::{Match, ReverseSearcher, Searcher, Threads},
+    stream::StreamMatcher,
+};
+
+// Boyer–Moore–Horspool substring search over the UTF-8 bytes of the haystack.
+// Every occurrence of a non-empty needle starts on a char boundary, so the
+// returned offsets can be used to slice the haystack directly.
+#[derive(Debug, Clone)]
+pub struct Horspool {
+    needle: String,
+    shifts: Box<[usize; 256]>,
+}
+
+impl Horspool {
+    pub fn new(needle: &str) -> Self {
+        let bytes = needle.as_bytes();
+        let mut shifts = Box::new([bytes.len().max(1); 256]);
+        if let Some((_, init)) = bytes.split_last() {
+            for (i, &b) in init.iter().enumerate() {
+                shifts[b as usize] = bytes.len() - 1 - i;
+            }
+        }
+
+        Self {
+            needle: needle.to_string(),
+            shifts,
+        }
+    }
+
+    pub fn needle(&self) -> &str {
+        &self.needle
+    }
+
+    pub fn find(&self, haystack: &str) -> Option<usize> {
+        self.find_at(haystack, 0)
+    }
+
+    pub fn find_at(&self, haystack: &str, start: usize) -> Option<usize> {
+        self.find_bytes_at(haystack.as_bytes(), start)
+    }
+
+    pub(crate) fn find_bytes_at(&self, haystack: &[u8], start: usize) -> Option<usize> {
+        let needle = self.needle.as_bytes();
+        let Some((&last, _)) = needle.split_last() else {
+            return (start <= haystack.len()).then_some(start);
+        };
+
+        let mut at = start;
+        while at + needle.len() <= haystack.len() {
+            let window_last = haystack[at + needle.len() - 1];
+            if window_last == last && &haystack[at..at + needle.len()] == needle {
+                return Some(at);
+            }
+            at += self.shifts[window_last as usize];
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Literal {
+    searcher: Horspool,
+}
+
+impl Literal {
+    pub fn new(literal: &str) -> Self {
+        Self {
+            searcher: Horspool::new(literal),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.searcher.needle()
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        input == self.searcher.needle()
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_literal(self)
+    }
+}
+
+// A thread holds how much of the literal it has matched, for searches that
+// cannot look ahead with the substring search.
+impl Threads for Literal {
+    type State = usize;
+    type Held = HashSet<usize>;
+
+    fn start_state(&self) -> usize {
+        0
+    }
+
+    fn next_states(&self, matched: usize, c: char, mut push: impl FnMut(usize)) {
+        if self.as_str()[matched..].starts_with(c) {
+            push(matched + c.len_utf8());
+        }
+    }
+
+    fn hold(&self, held: &mut HashSet<usize>, matched: usize) -> Option<usize> {
+        held.insert(matched).then_some(matched)
+    }
+
+    fn release(&self, held: &mut HashSet<usize>) {
+        held.clear();
+    }
+
+    fn thread_accepts(&self, matched: usize) -> bool {
+        matched == self.as_str().len()
+    }
+}
+
+impl Searcher for Literal {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        let found = self.searcher.find_at(haystack, start)?;
+        Some(Match::new(
+            haystack,
+            found,
+            found + self.searcher.needle().len(),
+        ))
+    }
+}
+
+impl ReverseSearcher for Literal {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        let found = haystack[..end].rfind(self.searcher.needle())?;
+        Some(Match::new(
+            haystack,
+            found,
+            found + self.searcher.needle().len(),
+        ))
+    }
+}
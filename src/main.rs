@@ -1,12 +1,7 @@
-use dumb_regex::regex::RegularExpression;
+use dumb_regex::regex::{Error, Regex};
 
-fn main() -> Result<(), String> {
-    let pattern = String::from("[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?");
-    let re = RegularExpression::new(pattern)
-        .to_epsilon_nfa()?
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
+fn main() -> Result<(), Error> {
+    let re = Regex::new("[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?")?;
 
     if re.is_match("3.14159") {
         println!("matches");
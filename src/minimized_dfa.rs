@@ -1,54 +1,553 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+    sync::OnceLock,
+    thread,
+};
 
-use crate::{Alphabet, StateId};
+use crate::{
+    Alphabet, StateId,
+    accel::Accelerator,
+    automaton::Automaton,
+    dfa::Dfa,
+    engine::EngineKind,
+    error::{MatchKindError, SymbolBuildError},
+    nfa::Nfa,
+    otherwise::{Otherwise, own_edges},
+    prefilter::Prefilter,
+    search::{
+        Anchors, Match, MatchKind, ReverseSearcher, Searcher, Threads, find_leftmost,
+        rfind_reversed,
+    },
+    stream::StreamMatcher,
+    symbol::Symbol,
+};
 
-type DFATransition = HashMap<Alphabet, StateId>;
+// The target of a missing transition while running a chunk.
+const DEAD: StateId = StateId::MAX;
 
+// Inputs are only split once every thread gets at least this many bytes.
+const MIN_CHUNK_LEN: usize = 1 << 12;
+
+const LANE_MERGE_INTERVAL: usize = 64;
+
+type DFATransition<A = Alphabet> = HashMap<A, StateId>;
+
+// State 0 is the dead state every missing transition leads to, so the states
+// added start at 1.
 #[derive(Debug)]
-pub struct MinimizedDfaBuilder {
-    pub transitions: Vec<DFATransition>,
+pub struct MinimizedDfaBuilder<A = Alphabet> {
+    pub(crate) transitions: Vec<DFATransition<A>>,
+    start: Option<StateId>,
+    end: HashSet<StateId>,
 }
 
-impl MinimizedDfaBuilder {
-    pub fn new() -> Self {
+impl<A: Symbol> Default for MinimizedDfaBuilder<A> {
+    fn default() -> Self {
         Self {
-            transitions: Vec::new(),
+            transitions: vec![DFATransition::new()],
+            start: None,
+            end: HashSet::new(),
         }
     }
+}
 
+impl MinimizedDfaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<A: Symbol> MinimizedDfaBuilder<A> {
     pub fn add_state(&mut self) -> StateId {
         let state: StateId = self.transitions.len();
         self.transitions.push(DFATransition::new());
         state
     }
+
+    pub fn add_transition(
+        &mut self,
+        from: StateId,
+        c: A,
+        to: StateId,
+    ) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(from)?;
+        self.check_state(to)?;
+        match self.transitions[from].get(&c) {
+            Some(&next) if next != to => Err(SymbolBuildError::Nondeterministic {
+                state: from,
+                symbol: c,
+            }),
+            _ => {
+                self.transitions[from].insert(c, to);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn set_start(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        self.start = Some(state);
+        Ok(())
+    }
+
+    pub fn set_accepting(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        self.end.insert(state);
+        Ok(())
+    }
+
+    // The automaton is minimized on the way, so equivalent states given by hand
+    // are merged.
+    pub fn build(self) -> Result<MinimizedDfa<A>, SymbolBuildError<A>> {
+        let start = self.start.ok_or(SymbolBuildError::MissingStart)?;
+        Ok(Dfa::new(self.transitions, start, self.end).to_minimized_dfa())
+    }
+
+    fn check_state(&self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        if state != 0 && state < self.transitions.len() {
+            Ok(())
+        } else {
+            Err(SymbolBuildError::UnknownState(state))
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct MinimizedDfa {
-    transitions: Vec<DFATransition>,
+pub struct MinimizedDfa<A = Alphabet> {
+    transitions: Vec<DFATransition<A>>,
     start: StateId,
     end: HashSet<StateId>,
+    otherwise: Otherwise<A>,
+    // Built on the first search, since only chars can be skipped over.
+    accelerators: OnceLock<Vec<Option<Accelerator>>>,
+    prefilter: Option<Prefilter>,
+    match_kind: MatchKind,
+    // Built on the first backward search.
+    reversed: OnceLock<Box<MinimizedDfa<A>>>,
 }
 
-impl MinimizedDfa {
-    pub fn new(transitions: Vec<DFATransition>, start: StateId, end: HashSet<StateId>) -> Self {
+impl<A: Symbol> MinimizedDfa<A> {
+    pub fn new(transitions: Vec<DFATransition<A>>, start: StateId, end: HashSet<StateId>) -> Self {
         Self {
             transitions,
             start,
             end,
+            otherwise: Otherwise::default(),
+            accelerators: OnceLock::new(),
+            prefilter: None,
+            match_kind: MatchKind::default(),
+            reversed: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn with_otherwise(mut self, otherwise: Otherwise<A>) -> Self {
+        self.otherwise = otherwise;
+        self
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn start(&self) -> StateId {
+        self.start
+    }
+
+    pub fn next_state(&self, state: StateId, c: A) -> Option<StateId> {
+        match self.transitions[state].get(&c) {
+            Some(&next) => Some(next),
+            None => self.otherwise.next_state(state, c),
         }
     }
 
+    pub fn is_accepting_state(&self, state: StateId) -> bool {
+        self.end.contains(&state)
+    }
+
+    pub fn accepting_states(&self) -> impl Iterator<Item = StateId> + '_ {
+        let mut states: Vec<StateId> = self.end.iter().copied().collect();
+        states.sort_unstable();
+        states.into_iter()
+    }
+
+    // Every edge as (from, symbol, to), ordered by state and then by symbol.
+    // A default edge is listed once for every symbol it is taken on.
+    pub fn transitions(&self) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+        self.transitions
+            .iter()
+            .enumerate()
+            .flat_map(|(from, transitions)| {
+                self.otherwise
+                    .edges(from, transitions)
+                    .map(move |(c, to)| (from, c, to))
+            })
+    }
+
+    // The edges of a state as (symbol, to) in symbol order, with the default
+    // edge labelled by the smallest symbol it is taken on rather than by all
+    // of them.
+    pub(crate) fn first_edges(&self, state: StateId) -> Vec<(A, StateId)> {
+        let transitions = &self.transitions[state];
+        let mut edges: Vec<(A, StateId)> = transitions.iter().map(|(&c, &to)| (c, to)).collect();
+        if let Some(to) = self.otherwise.target(state) {
+            edges.extend(
+                self.otherwise
+                    .symbols(|c| transitions.contains_key(c))
+                    .next()
+                    .map(|c| (c, to)),
+            );
+        }
+        edges.sort_unstable();
+        edges
+    }
+
+    // Concatenation and the repetitions go through the NFA and are minimized
+    // again.
+    fn to_nfa(&self) -> Nfa<A> {
+        Nfa::from_edges(
+            self.transitions.len(),
+            own_edges(&self.transitions),
+            self.start,
+            &self.end,
+        )
+        .with_defaults(self.otherwise.default_edges(&self.transitions))
+    }
+
+    pub fn concat(&self, other: &MinimizedDfa<A>) -> MinimizedDfa<A> {
+        self.to_nfa()
+            .concat(&other.to_nfa())
+            .to_dfa()
+            .to_minimized_dfa()
+    }
+
+    pub fn star(&self) -> MinimizedDfa<A> {
+        self.to_nfa().star().to_dfa().to_minimized_dfa()
+    }
+
+    pub fn plus(&self) -> MinimizedDfa<A> {
+        self.to_nfa().plus().to_dfa().to_minimized_dfa()
+    }
+
+    pub fn optional(&self) -> MinimizedDfa<A> {
+        self.to_nfa().optional().to_dfa().to_minimized_dfa()
+    }
+
+    // The product constructions are done on the DFA and minimized again.
+    fn to_dfa(&self) -> Dfa<A> {
+        Dfa::new(self.transitions.clone(), self.start, self.end.clone())
+            .with_otherwise(self.otherwise.clone())
+    }
+
+    pub fn intersect(&self, other: &MinimizedDfa<A>) -> MinimizedDfa<A> {
+        self.to_dfa().intersect(&other.to_dfa()).to_minimized_dfa()
+    }
+
+    pub fn union(&self, other: &MinimizedDfa<A>) -> MinimizedDfa<A> {
+        self.to_dfa().union(&other.to_dfa()).to_minimized_dfa()
+    }
+
+    pub fn difference(&self, other: &MinimizedDfa<A>) -> MinimizedDfa<A> {
+        self.to_dfa().difference(&other.to_dfa()).to_minimized_dfa()
+    }
+
+    pub fn symmetric_difference(&self, other: &MinimizedDfa<A>) -> MinimizedDfa<A> {
+        self.to_dfa()
+            .symmetric_difference(&other.to_dfa())
+            .to_minimized_dfa()
+    }
+
+    pub fn complement(&self, universe: &[RangeInclusive<A>]) -> MinimizedDfa<A> {
+        self.to_dfa().complement(universe).to_minimized_dfa()
+    }
+
+    pub fn reverse(&self) -> MinimizedDfa<A> {
+        Nfa::from_reversed_edges(
+            self.transitions.len(),
+            own_edges(&self.transitions),
+            &self.otherwise.default_edges(&self.transitions),
+            self.start,
+            &self.end,
+        )
+        .to_dfa()
+        .to_minimized_dfa()
+    }
+}
+
+impl MinimizedDfa {
+    pub fn with_prefilter(mut self, prefilter: Option<Prefilter>) -> Self {
+        self.prefilter = prefilter;
+        self
+    }
+
+    pub fn prefilter(&self) -> Option<&Prefilter> {
+        self.prefilter.as_ref()
+    }
+
+    pub fn with_match_kind(mut self, match_kind: MatchKind) -> Result<Self, MatchKindError> {
+        if match_kind == MatchKind::LeftmostFirst {
+            return Err(MatchKindError {
+                engine: EngineKind::MinimizedDfa,
+                match_kind,
+            });
+        }
+        self.match_kind = match_kind;
+        Ok(self)
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    pub fn accelerator(&self, state: StateId) -> Option<&Accelerator> {
+        self.accelerators()[state].as_ref()
+    }
+
+    fn accelerators(&self) -> &[Option<Accelerator>] {
+        self.accelerators.get_or_init(|| {
+            (0..self.transitions.len())
+                .map(|state| Accelerator::new(state, |c| self.next_state(state, c)))
+                .collect()
+        })
+    }
+
     pub fn is_match(&self, input: &str) -> bool {
+        let accelerators = self.accelerators();
         let mut curr: StateId = self.start;
-        for c in input.chars() {
-            if let Some(&next) = self.transitions[curr].get(&c) {
+        let mut at = 0;
+        while at < input.len() {
+            if let Some(accelerator) = &accelerators[curr] {
+                at = accelerator.skip(input.as_bytes(), at);
+                if at == input.len() {
+                    break;
+                }
+            }
+
+            let c = next_char(input, at);
+            if let Some(next) = self.next_state(curr, c) {
                 curr = next;
             } else {
                 return false;
             }
+            at += c.len_utf8();
         }
 
         self.end.contains(&curr)
     }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_minimized_dfa(self)
+    }
+
+    // Splits the input into chunks matched on separate threads. Every chunk but
+    // the first is run from all states at once, and the resulting state
+    // mappings are chained together in order to find the final state.
+    pub fn is_match_parallel(&self, input: &str, threads: usize) -> bool {
+        let chunk_count = threads.min(input.len() / MIN_CHUNK_LEN).max(1);
+        if chunk_count == 1 {
+            return self.is_match(input);
+        }
+
+        let mut boundaries: Vec<usize> = vec![0];
+        for i in 1..chunk_count {
+            let mut boundary = i * input.len() / chunk_count;
+            while !input.is_char_boundary(boundary) {
+                boundary += 1;
+            }
+            boundaries.push(boundary);
+        }
+        boundaries.push(input.len());
+
+        let (first_state, mappings) = thread::scope(|scope| {
+            let workers: Vec<_> = boundaries[1..]
+                .windows(2)
+                .map(|window| {
+                    let chunk = &input[window[0]..window[1]];
+                    scope.spawn(move || self.chunk_mapping(chunk))
+                })
+                .collect();
+
+            let first_state = self.run(self.start, &input[..boundaries[1]]);
+            let mappings: Vec<Vec<StateId>> = workers
+                .into_iter()
+                .map(|worker| worker.join().expect("chunk worker panicked"))
+                .collect();
+            (first_state, mappings)
+        });
+
+        let mut curr = first_state;
+        for mapping in &mappings {
+            if curr == DEAD {
+                return false;
+            }
+            curr = mapping[curr];
+        }
+
+        curr != DEAD && self.end.contains(&curr)
+    }
+
+    fn run(&self, state: StateId, input: &str) -> StateId {
+        let mut curr = state;
+        for c in input.chars() {
+            match self.next_state(curr, c) {
+                Some(next) => curr = next,
+                None => return DEAD,
+            }
+        }
+        curr
+    }
+
+    // Maps every state to the state the chunk leads it to. Starting states that
+    // reach the same state are merged periodically, so that a chunk costs about
+    // as much as a single run once the lanes have converged.
+    fn chunk_mapping(&self, chunk: &str) -> Vec<StateId> {
+        let mut lanes: Vec<StateId> = (0..self.transitions.len()).collect();
+        let mut lane_of_state: Vec<usize> = (0..self.transitions.len()).collect();
+        for (i, c) in chunk.chars().enumerate() {
+            for lane in lanes.iter_mut().filter(|lane| **lane != DEAD) {
+                *lane = self.next_state(*lane, c).unwrap_or(DEAD);
+            }
+
+            if i % LANE_MERGE_INTERVAL == LANE_MERGE_INTERVAL - 1 {
+                merge_lanes(&mut lanes, &mut lane_of_state);
+            }
+        }
+
+        lane_of_state.iter().map(|&lane| lanes[lane]).collect()
+    }
+
+    pub(crate) fn find_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        find_leftmost(
+            self,
+            haystack,
+            start,
+            self.prefilter.as_ref(),
+            self.match_kind,
+            anchors,
+        )
+    }
+
+    pub(crate) fn rfind_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        end: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let reversed = self.reversed.get_or_init(|| Box::new(self.reverse()));
+        rfind_reversed(haystack, end, anchors, |haystack, start, anchors| {
+            reversed.find_anchored_at(haystack, start, anchors)
+        })
+    }
+}
+
+impl Threads for MinimizedDfa {
+    type State = StateId;
+    type Held = HashSet<StateId>;
+
+    fn start_state(&self) -> StateId {
+        self.start
+    }
+
+    fn next_states(&self, state: StateId, c: char, push: impl FnMut(StateId)) {
+        self.next_state(state, c).into_iter().for_each(push);
+    }
+
+    fn hold(&self, held: &mut HashSet<StateId>, state: StateId) -> Option<StateId> {
+        held.insert(state).then_some(state)
+    }
+
+    fn release(&self, held: &mut HashSet<StateId>) {
+        held.clear();
+    }
+
+    fn thread_accepts(&self, state: StateId) -> bool {
+        self.end.contains(&state)
+    }
+
+    fn skip(&self, state: StateId, buffered: &[u8]) -> usize {
+        match &self.accelerators()[state] {
+            Some(accelerator) => accelerator.skip(buffered, 0),
+            None => 0,
+        }
+    }
+}
+
+impl Searcher for MinimizedDfa {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        self.find_anchored_at(haystack, start, &Anchors::default())
+    }
+}
+
+impl<A: Symbol> Automaton for MinimizedDfa<A> {
+    type Symbol = A;
+    type Label<'a>
+        = A
+    where
+        A: 'a;
+    type States = Option<StateId>;
+
+    fn state_count(&self) -> usize {
+        MinimizedDfa::state_count(self)
+    }
+
+    fn start(&self) -> StateId {
+        MinimizedDfa::start(self)
+    }
+
+    fn is_accepting_state(&self, state: StateId) -> bool {
+        MinimizedDfa::is_accepting_state(self, state)
+    }
+
+    fn transitions(&self) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+        MinimizedDfa::transitions(self)
+    }
+
+    fn start_states(&self) -> Option<StateId> {
+        Some(self.start)
+    }
+
+    fn step(&self, states: &mut Option<StateId>, c: A) {
+        *states = states.and_then(|state| self.next_state(state, c));
+    }
+
+    fn is_accepting(&self, states: &Option<StateId>) -> bool {
+        states.is_some_and(|state| self.end.contains(&state))
+    }
+}
+
+impl ReverseSearcher for MinimizedDfa {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        self.rfind_anchored_at(haystack, end, &Anchors::default())
+    }
+}
+
+fn merge_lanes(lanes: &mut Vec<StateId>, lane_of_state: &mut [usize]) {
+    let mut merged_lanes: Vec<StateId> = Vec::new();
+    let mut lane_of_target: HashMap<StateId, usize> = HashMap::new();
+    let remapped: Vec<usize> = lanes
+        .iter()
+        .map(|&target| {
+            *lane_of_target.entry(target).or_insert_with(|| {
+                merged_lanes.push(target);
+                merged_lanes.len() - 1
+            })
+        })
+        .collect();
+
+    for lane in lane_of_state.iter_mut() {
+        *lane = remapped[*lane];
+    }
+    *lanes = merged_lanes;
+}
+
+fn next_char(haystack: &str, at: usize) -> char {
+    haystack[at..]
+        .chars()
+        .next()
+        .expect("offset is a char boundary inside the haystack")
 }
@@ -1,121 +1,797 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    sync::OnceLock,
+};
 
 use crate::{
     Alphabet, StateId,
+    automaton::Automaton,
     dfa::{Dfa, DfaBuilder},
+    engine::EngineKind,
+    error::{MatchKindError, SymbolBuildError},
+    lazy_dfa::LazyDfa,
+    limits::{DfaError, DfaLimits},
+    otherwise::{DefaultEdge, DefaultEdges, Otherwise},
+    search::{
+        Anchors, Match, MatchKind, ReverseSearcher, Searcher, Threads, find_leftmost,
+        rfind_reversed,
+    },
+    sparse_set::Cache,
+    state_set::StateSet,
+    stream::StreamMatcher,
+    symbol::Symbol,
 };
 
-type NFATransition = HashMap<Alphabet, HashSet<StateId>>;
+type NFATransition<A = Alphabet> = HashMap<A, HashSet<StateId>>;
 
-pub struct NfaBuilder {
-    pub transitions: Vec<NFATransition>,
+pub struct NfaBuilder<A = Alphabet> {
+    pub(crate) transitions: Vec<NFATransition<A>>,
+    start: Option<StateId>,
+    end: HashSet<StateId>,
+    defaults: DefaultEdges<A>,
 }
 
-impl NfaBuilder {
-    pub fn new() -> Self {
+impl<A: Symbol> Default for NfaBuilder<A> {
+    fn default() -> Self {
         Self {
             transitions: Vec::new(),
+            start: None,
+            end: HashSet::new(),
+            defaults: DefaultEdges::default(),
         }
     }
+}
 
+impl NfaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<A: Symbol> NfaBuilder<A> {
     pub fn add_state(&mut self) -> StateId {
         let state: StateId = self.transitions.len();
         self.transitions.push(NFATransition::new());
         state
     }
+
+    pub fn add_transition(
+        &mut self,
+        from: StateId,
+        c: A,
+        to: StateId,
+    ) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(from)?;
+        self.check_state(to)?;
+        self.transitions[from].entry(c).or_default().insert(to);
+        Ok(())
+    }
+
+    pub fn set_start(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        self.start = Some(state);
+        Ok(())
+    }
+
+    pub fn set_accepting(&mut self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        self.check_state(state)?;
+        self.end.insert(state);
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<Nfa<A>, SymbolBuildError<A>> {
+        let start = self.start.ok_or(SymbolBuildError::MissingStart)?;
+        Ok(Nfa::new(self.transitions, start, self.end))
+    }
+
+    // Copies in the states of `nfa` after the ones already added, returning
+    // what its state ids were shifted by. Default edges over another domain
+    // than the ones copied in before are spelled out symbol by symbol.
+    fn append(&mut self, nfa: &Nfa<A>) -> StateId {
+        let offset = self.transitions.len();
+        self.transitions
+            .extend(nfa.transitions.iter().map(|transitions| {
+                transitions
+                    .iter()
+                    .map(|(&c, targets)| (c, targets.iter().map(|&to| to + offset).collect()))
+                    .collect()
+            }));
+
+        if self.defaults.is_empty() {
+            self.defaults = DefaultEdges::new(nfa.defaults.domain());
+        }
+        if self.defaults.domain() == nfa.defaults.domain() {
+            for (from, edge) in nfa.defaults.iter() {
+                self.defaults.insert(
+                    from + offset,
+                    DefaultEdge {
+                        except: edge.except.clone(),
+                        to: edge.to + offset,
+                    },
+                );
+            }
+        } else {
+            for (from, c, to) in nfa.defaults.symbol_edges() {
+                self.transitions[from + offset]
+                    .entry(c)
+                    .or_default()
+                    .insert(to + offset);
+            }
+        }
+        offset
+    }
+
+    // Gives `to` every edge leaving `from`, so reaching `to` also counts as
+    // having reached `from`.
+    fn copy_edges(&mut self, from: StateId, to: StateId) {
+        let edges = self.transitions[from].clone();
+        for (c, targets) in edges {
+            self.transitions[to].entry(c).or_default().extend(targets);
+        }
+        for edge in self.defaults.edges(from).to_vec() {
+            self.defaults.insert(to, edge);
+        }
+    }
+
+    fn build_nfa(self, start: StateId) -> Nfa<A> {
+        Nfa::new(self.transitions, start, self.end).with_defaults(self.defaults)
+    }
+
+    fn check_state(&self, state: StateId) -> Result<(), SymbolBuildError<A>> {
+        if state < self.transitions.len() {
+            Ok(())
+        } else {
+            Err(SymbolBuildError::UnknownState(state))
+        }
+    }
 }
 
-#[derive(Debug)]
-pub struct Nfa {
-    transitions: Vec<NFATransition>,
+#[derive(Debug, Clone)]
+pub struct Nfa<A = Alphabet> {
+    transitions: Vec<NFATransition<A>>,
     start: StateId,
     end: HashSet<StateId>,
+    // Only left on the way from one DFA to another, which the searches never
+    // see.
+    defaults: DefaultEdges<A>,
+    match_kind: MatchKind,
+    reversed: OnceLock<Box<Nfa<A>>>,
 }
 
-impl Nfa {
-    pub fn new(transitions: Vec<NFATransition>, start: StateId, end: HashSet<StateId>) -> Self {
+impl<A: Symbol> Nfa<A> {
+    pub fn new(transitions: Vec<NFATransition<A>>, start: StateId, end: HashSet<StateId>) -> Self {
         Self {
             transitions,
             start,
             end,
+            defaults: DefaultEdges::default(),
+            match_kind: MatchKind::default(),
+            reversed: OnceLock::new(),
         }
     }
 
-    pub fn is_match(&self, input: &str) -> bool {
-        let mut current_states: HashSet<StateId> = HashSet::new();
-        current_states.insert(self.start);
-        for c in input.chars() {
-            let mut next_states: HashSet<StateId> = HashSet::new();
-            for &curr in &current_states {
-                if let Some(adj) = self.transitions[curr].get(&c) {
-                    for &next in adj {
-                        next_states.insert(next);
-                    }
-                }
+    pub(crate) fn with_defaults(mut self, defaults: DefaultEdges<A>) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    // Builds the NFA of the reversed language from the edges and default edges
+    // of an automaton with the given start and end states. A fresh start state
+    // takes over the outgoing edges of every reversed end state.
+    pub(crate) fn from_reversed_edges<I: IntoIterator<Item = (StateId, A, StateId)>>(
+        state_count: usize,
+        edges: I,
+        defaults: &DefaultEdges<A>,
+        start: StateId,
+        end: &HashSet<StateId>,
+    ) -> Self {
+        let mut nfa_builder = NfaBuilder::default();
+        for _ in 0..state_count {
+            nfa_builder.add_state();
+        }
+        let nfa_start = nfa_builder.add_state();
+
+        for (from, c, to) in edges {
+            nfa_builder.transitions[to]
+                .entry(c)
+                .or_default()
+                .insert(from);
+            if end.contains(&to) {
+                nfa_builder.transitions[nfa_start]
+                    .entry(c)
+                    .or_default()
+                    .insert(from);
             }
-            current_states = next_states;
         }
 
-        for end in &self.end {
-            if current_states.contains(end) {
-                return true;
+        nfa_builder.defaults = DefaultEdges::new(defaults.domain());
+        for (from, edge) in defaults.iter() {
+            let reversed = DefaultEdge {
+                except: edge.except.clone(),
+                to: from,
+            };
+            if end.contains(&edge.to) {
+                nfa_builder.defaults.insert(nfa_start, reversed.clone());
             }
+            nfa_builder.defaults.insert(edge.to, reversed);
         }
-        false
+
+        nfa_builder.end = HashSet::from([start]);
+        if end.contains(&start) {
+            nfa_builder.end.insert(nfa_start);
+        }
+
+        nfa_builder.build_nfa(nfa_start)
     }
 
-    pub fn to_dfa(&self) -> Dfa {
-        let mut dfa_builder = DfaBuilder::new();
+    pub fn reverse(&self) -> Nfa<A> {
+        let edges = self
+            .transitions
+            .iter()
+            .enumerate()
+            .flat_map(|(from, transitions)| {
+                transitions
+                    .iter()
+                    .flat_map(move |(&c, targets)| targets.iter().map(move |&to| (from, c, to)))
+            });
+        Nfa::from_reversed_edges(
+            self.transitions.len(),
+            edges,
+            &self.defaults,
+            self.start,
+            &self.end,
+        )
+    }
 
-        dfa_builder.add_state();
+    // Builds an NFA from the edges of an automaton with the given start and
+    // end states.
+    pub(crate) fn from_edges<I: IntoIterator<Item = (StateId, A, StateId)>>(
+        state_count: usize,
+        edges: I,
+        start: StateId,
+        end: &HashSet<StateId>,
+    ) -> Self {
+        let mut transitions: Vec<NFATransition<A>> = vec![NFATransition::new(); state_count];
+        for (from, c, to) in edges {
+            transitions[from].entry(c).or_default().insert(to);
+        }
+        Nfa::new(transitions, start, end.clone())
+    }
 
-        let mut subset_to_dfa_state: HashMap<BTreeSet<StateId>, StateId> = HashMap::new();
+    // The Kleene operations below work like the ones on fragments of the
+    // εNFA, with a copy of the edges of a start state standing in for an
+    // epsilon move to it.
+
+    pub fn union(&self, other: &Nfa<A>) -> Nfa<A> {
+        let mut nfa_builder = NfaBuilder::default();
+        let start = nfa_builder.add_state();
+        for nfa in [self, other] {
+            let offset = nfa_builder.append(nfa);
+            nfa_builder.copy_edges(offset + nfa.start, start);
+            nfa_builder
+                .end
+                .extend(nfa.end.iter().map(|&state| offset + state));
+            if nfa.end.contains(&nfa.start) {
+                nfa_builder.end.insert(start);
+            }
+        }
+        nfa_builder.build_nfa(start)
+    }
+
+    pub fn concat(&self, other: &Nfa<A>) -> Nfa<A> {
+        let mut nfa_builder = NfaBuilder::default();
+        let left = nfa_builder.append(self);
+        let right = nfa_builder.append(other);
+        for &state in &self.end {
+            nfa_builder.copy_edges(right + other.start, left + state);
+        }
+        nfa_builder
+            .end
+            .extend(other.end.iter().map(|&state| right + state));
+        if other.end.contains(&other.start) {
+            nfa_builder
+                .end
+                .extend(self.end.iter().map(|&state| left + state));
+        }
+        nfa_builder.build_nfa(left + self.start)
+    }
+
+    pub fn star(&self) -> Nfa<A> {
+        self.plus().optional()
+    }
+
+    pub fn plus(&self) -> Nfa<A> {
+        let mut nfa_builder = NfaBuilder::default();
+        let offset = nfa_builder.append(self);
+        for &state in &self.end {
+            nfa_builder.copy_edges(offset + self.start, offset + state);
+        }
+        nfa_builder
+            .end
+            .extend(self.end.iter().map(|&state| offset + state));
+        nfa_builder.build_nfa(offset + self.start)
+    }
+
+    // A fresh start state, since accepting the old one would also accept
+    // whatever leads back to it.
+    pub fn optional(&self) -> Nfa<A> {
+        let mut nfa_builder = NfaBuilder::default();
+        let start = nfa_builder.add_state();
+        let offset = nfa_builder.append(self);
+        nfa_builder.copy_edges(offset + self.start, start);
+        nfa_builder
+            .end
+            .extend(self.end.iter().map(|&state| offset + state));
+        nfa_builder.end.insert(start);
+        nfa_builder.build_nfa(start)
+    }
+
+    pub fn start(&self) -> StateId {
+        self.start
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_accepting_state(&self, state: StateId) -> bool {
+        self.end.contains(&state)
+    }
+
+    pub fn accepting_states(&self) -> impl Iterator<Item = StateId> + '_ {
+        let mut states: Vec<StateId> = self.end.iter().copied().collect();
+        states.sort_unstable();
+        states.into_iter()
+    }
+
+    // Every edge as (from, symbol, to), ordered by state, symbol and target.
+    pub fn transitions(&self) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+        self.transitions
+            .iter()
+            .enumerate()
+            .flat_map(|(from, transitions)| {
+                let mut edges: Vec<(A, StateId)> = transitions
+                    .iter()
+                    .flat_map(|(&c, targets)| targets.iter().map(move |&to| (c, to)))
+                    .collect();
+                edges.sort_unstable();
+                edges.into_iter().map(move |(c, to)| (from, c, to))
+            })
+    }
+
+    pub(crate) fn contains_end<I: IntoIterator<Item = StateId>>(&self, states: I) -> bool {
+        states.into_iter().any(|state| self.end.contains(&state))
+    }
+
+    pub fn to_dfa(&self) -> Dfa<A> {
+        self.to_dfa_with_limits(&DfaLimits::new())
+            .expect("unlimited DFA construction cannot fail")
+    }
+
+    pub fn to_dfa_with_limits(&self, limits: &DfaLimits) -> Result<Dfa<A>, DfaError> {
+        let n = self.transitions.len();
+        let transition_intervals = self.transition_intervals();
+
+        let mut end = StateSet::new(n);
+        for &state in &self.end {
+            end.insert(state);
+        }
+
+        let mut dfa_builder = DfaBuilder::default();
+        let mut dfa_end: HashSet<StateId> = HashSet::new();
+        let mut otherwise = Otherwise::new(self.defaults.domain());
+        let mut total_transitions: usize = 0;
+
+        let mut subset_to_dfa_state: HashMap<StateSet, StateId> = HashMap::new();
 
         let dfa_start = dfa_builder.add_state();
-        let start: BTreeSet<StateId> = BTreeSet::from([self.start]);
+        let mut start = StateSet::new(n);
+        start.insert(self.start);
+        if start.intersects(&end) {
+            dfa_end.insert(dfa_start);
+        }
         subset_to_dfa_state.insert(start.clone(), dfa_start);
 
-        let mut stack: Vec<BTreeSet<StateId>> = Vec::new();
-        stack.push(start);
-        while let Some(curr_states) = stack.pop() {
+        let mut queue: VecDeque<StateSet> = VecDeque::from([start]);
+        while let Some(curr_states) = queue.pop_front() {
             let &curr_dfa_state = subset_to_dfa_state.get(&curr_states).expect(
                 "curr_states should always be in subset_to_dfa_state due to a previous iteration",
             );
 
-            let mut current_transitions: HashMap<Alphabet, BTreeSet<StateId>> = HashMap::new();
-            for curr in curr_states {
-                for (&alphabet, next_states) in &self.transitions[curr] {
-                    current_transitions
-                        .entry(alphabet)
-                        .or_default()
-                        .extend(next_states);
-                }
-            }
-
-            for (alphabet, next_states) in current_transitions {
+            let moves: Vec<Move<A>> = if curr_states
+                .iter()
+                .all(|state| self.defaults.edges(state).is_empty())
+            {
+                partition(&transition_intervals, &curr_states, n)
+                    .into_iter()
+                    .map(|(from, to, next_states)| (Some((from, to)), next_states))
+                    .collect()
+            } else {
+                self.default_moves(&curr_states)
+            };
+            for (segment, next_states) in moves {
                 let next_dfa_state = match subset_to_dfa_state.get(&next_states) {
+                    // The dead state of the builder.
+                    _ if next_states.is_empty() => 0,
                     Some(&dfa_state) => dfa_state,
                     None => {
                         let state = dfa_builder.add_state();
+                        limits.check_states(dfa_builder.transitions.len() - 1)?;
+                        if next_states.intersects(&end) {
+                            dfa_end.insert(state);
+                        }
                         subset_to_dfa_state.insert(next_states.clone(), state);
-                        stack.push(next_states);
+                        queue.push_back(next_states);
                         state
                     }
                 };
-                dfa_builder.transitions[curr_dfa_state].insert(alphabet, next_dfa_state);
+                match segment {
+                    Some((from, to)) => {
+                        for c in segment_symbols(from, to) {
+                            dfa_builder.transitions[curr_dfa_state].insert(c, next_dfa_state);
+                            total_transitions += 1;
+                            limits.check_transitions(total_transitions)?;
+                        }
+                    }
+                    None => {
+                        otherwise.insert(curr_dfa_state, next_dfa_state);
+                        total_transitions += 1;
+                        limits.check_transitions(total_transitions)?;
+                    }
+                }
             }
+
+            limits.check_interrupted()?;
         }
 
-        let mut dfa_end: HashSet<StateId> = HashSet::new();
-        for (subset, &dfa_state) in &subset_to_dfa_state {
-            for &end in &self.end {
-                if subset.contains(&end) {
-                    dfa_end.insert(dfa_state);
-                    break;
+        Ok(Dfa::new(dfa_builder.transitions, dfa_start, dfa_end).with_otherwise(otherwise))
+    }
+
+    // The moves out of a subset where some state has default edges. The
+    // symbols a state of the subset singles out are followed one at a time,
+    // and the rest of the domain through a single default edge.
+    fn default_moves(&self, states: &StateSet) -> Vec<Move<A>> {
+        let n = self.transitions.len();
+        let mut symbols: Vec<A> = states
+            .iter()
+            .flat_map(|state| {
+                self.transitions[state].keys().copied().chain(
+                    self.defaults
+                        .edges(state)
+                        .iter()
+                        .flat_map(|edge| edge.except.iter().copied()),
+                )
+            })
+            .collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+
+        let mut moves: Vec<Move<A>> = Vec::with_capacity(symbols.len() + 1);
+        for &c in &symbols {
+            let mut next_states = StateSet::new(n);
+            for state in states.iter() {
+                for &next in self.transitions[state].get(&c).into_iter().flatten() {
+                    next_states.insert(next);
+                }
+                for next in self.defaults.targets(state, c) {
+                    next_states.insert(next);
+                }
+            }
+            // A symbol of the domain leading nowhere still needs an edge of its
+            // own, or the default edge would be taken on it.
+            if !next_states.is_empty() || self.defaults.contains(c) {
+                moves.push((Some(((c, false), (c, true))), next_states));
+            }
+        }
+        if self.defaults.has_other_symbol(&symbols) {
+            let mut next_states = StateSet::new(n);
+            for state in states.iter() {
+                for edge in self.defaults.edges(state) {
+                    next_states.insert(edge.to);
+                }
+            }
+            moves.push((None, next_states));
+        }
+        moves
+    }
+
+    fn transition_intervals(&self) -> Vec<Vec<Interval<A>>> {
+        let n = self.transitions.len();
+
+        let mut transition_intervals: Vec<Vec<Interval<A>>> = Vec::with_capacity(n);
+        for transitions in &self.transitions {
+            let mut labels: Vec<(A, StateSet)> = transitions
+                .iter()
+                .map(|(&c, next_states)| {
+                    let mut targets = StateSet::new(n);
+                    for &next in next_states {
+                        targets.insert(next);
+                    }
+                    (c, targets)
+                })
+                .collect();
+            labels.sort_unstable_by_key(|&(c, _)| c);
+
+            let mut intervals: Vec<Interval<A>> = Vec::new();
+            for (c, targets) in labels {
+                match intervals.last_mut() {
+                    Some((_, high, last_targets))
+                        if high.successor() == Some(c) && *last_targets == targets =>
+                    {
+                        *high = c;
+                    }
+                    _ => intervals.push((c, c, targets)),
+                }
+            }
+            transition_intervals.push(intervals);
+        }
+        transition_intervals
+    }
+}
+
+impl Nfa {
+    // The transitions of a state are an unordered set, so leftmost-first needs
+    // the εNFA the NFA was built from.
+    pub fn with_match_kind(mut self, match_kind: MatchKind) -> Result<Self, MatchKindError> {
+        if match_kind == MatchKind::LeftmostFirst {
+            return Err(MatchKindError {
+                engine: EngineKind::Nfa,
+                match_kind,
+            });
+        }
+        self.match_kind = match_kind;
+        Ok(self)
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    pub fn create_cache(&self) -> Cache {
+        Cache::new(self.transitions.len())
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        self.is_match_with_cache(&mut self.create_cache(), input)
+    }
+
+    pub fn is_match_with_cache(&self, cache: &mut Cache, input: &str) -> bool {
+        self.is_match_from(cache, [self.start], input)
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        StreamMatcher::for_nfa(self)
+    }
+
+    pub(crate) fn is_match_from<I: IntoIterator<Item = StateId>>(
+        &self,
+        cache: &mut Cache,
+        states: I,
+        input: &str,
+    ) -> bool {
+        self.start_cache(cache, states);
+        for c in input.chars() {
+            self.step_cache(cache, c);
+
+            if cache.current.is_empty() {
+                return false;
+            }
+        }
+
+        self.is_cache_accepting(cache)
+    }
+
+    pub(crate) fn start_cache<I: IntoIterator<Item = StateId>>(
+        &self,
+        cache: &mut Cache,
+        states: I,
+    ) {
+        cache.reset(self.transitions.len());
+
+        for state in states {
+            cache.current.insert(state);
+        }
+    }
+
+    pub(crate) fn step_cache(&self, cache: &mut Cache, c: Alphabet) {
+        let Cache { current, next, .. } = cache;
+        for &curr in current.iter() {
+            if let Some(adj) = self.transitions[curr].get(&c) {
+                for &next_state in adj {
+                    next.insert(next_state);
                 }
             }
         }
+        cache.swap();
+    }
+
+    pub(crate) fn is_cache_accepting(&self, cache: &Cache) -> bool {
+        self.contains_end(cache.current.iter().copied())
+    }
+
+    pub(crate) fn next_states(&self, current_states: &StateSet, c: Alphabet) -> StateSet {
+        let mut next_states = StateSet::new(self.transitions.len());
+        for curr in current_states.iter() {
+            if let Some(adj) = self.transitions[curr].get(&c) {
+                for &next in adj {
+                    next_states.insert(next);
+                }
+            }
+        }
+        next_states
+    }
+
+    pub fn to_lazy_dfa(&self) -> LazyDfa {
+        LazyDfa::new(self.clone())
+    }
+
+    pub fn to_lazy_dfa_with_cache_capacity(&self, cache_capacity: usize) -> LazyDfa {
+        LazyDfa::with_cache_capacity(self.clone(), cache_capacity)
+    }
 
-        Dfa::new(dfa_builder.transitions, dfa_start, dfa_end)
+    pub(crate) fn find_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        start: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        find_leftmost(self, haystack, start, None, self.match_kind, anchors)
     }
+
+    pub(crate) fn rfind_anchored_at<'h>(
+        &self,
+        haystack: &'h str,
+        end: usize,
+        anchors: &Anchors,
+    ) -> Option<Match<'h>> {
+        let reversed = self.reversed.get_or_init(|| Box::new(self.reverse()));
+        rfind_reversed(haystack, end, anchors, |haystack, start, anchors| {
+            reversed.find_anchored_at(haystack, start, anchors)
+        })
+    }
+}
+
+// Each thread holds a single state, since the states of an NFA go on
+// independently of one another.
+impl Threads for Nfa {
+    type State = StateId;
+    type Held = HashSet<StateId>;
+
+    fn start_state(&self) -> StateId {
+        self.start
+    }
+
+    fn next_states(&self, state: StateId, c: char, push: impl FnMut(StateId)) {
+        self.transitions[state]
+            .get(&c)
+            .into_iter()
+            .flatten()
+            .copied()
+            .for_each(push);
+    }
+
+    fn hold(&self, held: &mut HashSet<StateId>, state: StateId) -> Option<StateId> {
+        held.insert(state).then_some(state)
+    }
+
+    fn release(&self, held: &mut HashSet<StateId>) {
+        held.clear();
+    }
+
+    fn thread_accepts(&self, state: StateId) -> bool {
+        self.end.contains(&state)
+    }
+}
+
+impl Searcher for Nfa {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        self.find_anchored_at(haystack, start, &Anchors::default())
+    }
+}
+
+impl ReverseSearcher for Nfa {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        self.rfind_anchored_at(haystack, end, &Anchors::default())
+    }
+}
+
+impl<A: Symbol> Automaton for Nfa<A> {
+    type Symbol = A;
+    type Label<'a>
+        = A
+    where
+        A: 'a;
+    type States = BTreeSet<StateId>;
+
+    fn state_count(&self) -> usize {
+        Nfa::state_count(self)
+    }
+
+    fn start(&self) -> StateId {
+        Nfa::start(self)
+    }
+
+    fn is_accepting_state(&self, state: StateId) -> bool {
+        Nfa::is_accepting_state(self, state)
+    }
+
+    fn transitions(&self) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+        Nfa::transitions(self)
+    }
+
+    fn start_states(&self) -> BTreeSet<StateId> {
+        BTreeSet::from([self.start])
+    }
+
+    fn step(&self, states: &mut BTreeSet<StateId>, c: A) {
+        *states = states
+            .iter()
+            .filter_map(|&state| self.transitions[state].get(&c))
+            .flatten()
+            .copied()
+            .collect();
+    }
+
+    fn is_accepting(&self, states: &BTreeSet<StateId>) -> bool {
+        self.contains_end(states.iter().copied())
+    }
+}
+
+type Interval<A> = (A, A, StateSet);
+
+// A boundary between two segments of symbols sits either right before a symbol
+// or right after it, so neighbouring symbols never need to be computed.
+type Boundary<A> = (A, bool);
+
+type Segment<A> = (Boundary<A>, Boundary<A>, StateSet);
+
+// The symbols between two boundaries, or the default edge for None, and the
+// states they lead to.
+type Move<A> = (Option<(Boundary<A>, Boundary<A>)>, StateSet);
+
+fn partition<A: Symbol>(
+    transition_intervals: &[Vec<Interval<A>>],
+    states: &StateSet,
+    n: usize,
+) -> Vec<Segment<A>> {
+    let mut boundaries: Vec<Boundary<A>> = Vec::new();
+    for state in states.iter() {
+        for &(low, high, _) in &transition_intervals[state] {
+            boundaries.push((low, false));
+            boundaries.push((high, true));
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut segments: Vec<StateSet> = vec![StateSet::new(n); boundaries.len().saturating_sub(1)];
+    for state in states.iter() {
+        for &(low, high, ref targets) in &transition_intervals[state] {
+            let first = boundaries.partition_point(|&boundary| boundary < (low, false));
+            let last = boundaries.partition_point(|&boundary| boundary < (high, true));
+            for segment in &mut segments[first..last] {
+                segment.union_with(targets);
+            }
+        }
+    }
+
+    boundaries
+        .windows(2)
+        .zip(segments)
+        .filter(|(_, segment)| !segment.is_empty())
+        .map(|(window, segment)| (window[0], window[1], segment))
+        .collect()
+}
+
+// The symbols between two boundaries.
+fn segment_symbols<A: Symbol>(from: Boundary<A>, to: Boundary<A>) -> impl Iterator<Item = A> {
+    let mut next = match from {
+        (c, false) => Some(c),
+        (c, true) => c.successor(),
+    };
+    std::iter::from_fn(move || {
+        let c = next.filter(|&c| (c, false) < to)?;
+        next = c.successor();
+        Some(c)
+    })
 }
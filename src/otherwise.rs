@@ -0,0 +1,251 @@
+use std::{collections::HashMap, iter::Peekable, ops::RangeInclusive};
+
+use crate::{
+    StateId,
+    symbol::{Symbol, range_symbols},
+};
+
+// The edges a DFA state takes on every symbol of `domain` it has no transition
+// of its own for. A complement over all of Unicode would otherwise need an
+// edge per char out of every state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Otherwise<A> {
+    // Sorted and disjoint.
+    domain: Vec<RangeInclusive<A>>,
+    targets: HashMap<StateId, StateId>,
+}
+
+impl<A> Default for Otherwise<A> {
+    fn default() -> Self {
+        Self {
+            domain: Vec::new(),
+            targets: HashMap::new(),
+        }
+    }
+}
+
+// The ranges sorted, with the overlapping ones merged.
+fn merge_ranges<A: Symbol>(domain: &[RangeInclusive<A>]) -> Vec<RangeInclusive<A>> {
+    let mut ranges: Vec<RangeInclusive<A>> = domain
+        .iter()
+        .filter(|range| !range.is_empty())
+        .cloned()
+        .collect();
+    ranges.sort_unstable_by_key(|range| *range.start());
+
+    let mut merged: Vec<RangeInclusive<A>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if *range.start() <= *last.end() => {
+                *last = *last.start()..=(*last.end()).max(*range.end());
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn domain_contains<A: Symbol>(domain: &[RangeInclusive<A>], c: A) -> bool {
+    let index = domain.partition_point(|range| *range.end() < c);
+    domain.get(index).is_some_and(|range| range.contains(&c))
+}
+
+// The edges of every state on the symbols it has an edge of its own for, in
+// no particular order.
+pub(crate) fn own_edges<A: Symbol>(
+    transitions: &[HashMap<A, StateId>],
+) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+    transitions
+        .iter()
+        .enumerate()
+        .flat_map(|(from, transitions)| transitions.iter().map(move |(&c, &to)| (from, c, to)))
+}
+
+impl<A: Symbol> Otherwise<A> {
+    pub(crate) fn new(domain: &[RangeInclusive<A>]) -> Self {
+        Self {
+            domain: merge_ranges(domain),
+            targets: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub(crate) fn domain(&self) -> &[RangeInclusive<A>] {
+        &self.domain
+    }
+
+    pub(crate) fn target(&self, state: StateId) -> Option<StateId> {
+        self.targets.get(&state).copied()
+    }
+
+    pub(crate) fn insert(&mut self, from: StateId, to: StateId) {
+        self.targets.insert(from, to);
+    }
+
+    // Where `state` goes on `c` when it has no transition of its own for it.
+    pub(crate) fn next_state(&self, state: StateId, c: A) -> Option<StateId> {
+        let to = self.target(state)?;
+        domain_contains(&self.domain, c).then_some(to)
+    }
+
+    // The same edges on an NFA, each one listing the symbols of the domain
+    // its state has an edge of its own for.
+    pub(crate) fn default_edges(&self, transitions: &[HashMap<A, StateId>]) -> DefaultEdges<A> {
+        let mut default_edges = DefaultEdges::new(&self.domain);
+        for (&from, &to) in &self.targets {
+            let mut except: Vec<A> = transitions[from]
+                .keys()
+                .copied()
+                .filter(|&c| domain_contains(&self.domain, c))
+                .collect();
+            except.sort_unstable();
+            default_edges.insert(from, DefaultEdge { except, to });
+        }
+        default_edges
+    }
+
+    // The symbols of the domain there is no edge of its own for, in order.
+    pub(crate) fn symbols<'a>(
+        &'a self,
+        has_edge: impl Fn(&A) -> bool + 'a,
+    ) -> impl Iterator<Item = A> + 'a {
+        self.domain
+            .iter()
+            .flat_map(range_symbols)
+            .filter(move |c| !has_edge(c))
+    }
+
+    // The edges of a state as (symbol, to) in symbol order, its own ones merged
+    // with the ones of the domain it has none for.
+    pub(crate) fn edges<'a>(
+        &'a self,
+        state: StateId,
+        transitions: &'a HashMap<A, StateId>,
+    ) -> impl Iterator<Item = (A, StateId)> + 'a {
+        let mut own: Vec<(A, StateId)> = transitions.iter().map(|(&c, &to)| (c, to)).collect();
+        own.sort_unstable();
+        let other = self.target(state).into_iter().flat_map(move |to| {
+            self.symbols(move |c| transitions.contains_key(c))
+                .map(move |c| (c, to))
+        });
+        Merge {
+            left: own.into_iter().peekable(),
+            right: other.peekable(),
+        }
+    }
+}
+
+// An edge of an NFA taken on every symbol of the domain but the sorted
+// `except` ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DefaultEdge<A> {
+    pub(crate) except: Vec<A>,
+    pub(crate) to: StateId,
+}
+
+// The default edges of an NFA, all over the same domain. Unlike the ones of a
+// DFA, each edge lists the symbols it is not taken on instead of leaving them
+// to the edges of its state, so reversing an edge keeps it a default edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DefaultEdges<A> {
+    // Sorted and disjoint.
+    domain: Vec<RangeInclusive<A>>,
+    edges: HashMap<StateId, Vec<DefaultEdge<A>>>,
+}
+
+impl<A> Default for DefaultEdges<A> {
+    fn default() -> Self {
+        Self {
+            domain: Vec::new(),
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<A: Symbol> DefaultEdges<A> {
+    pub(crate) fn new(domain: &[RangeInclusive<A>]) -> Self {
+        Self {
+            domain: merge_ranges(domain),
+            edges: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub(crate) fn domain(&self) -> &[RangeInclusive<A>] {
+        &self.domain
+    }
+
+    pub(crate) fn contains(&self, c: A) -> bool {
+        domain_contains(&self.domain, c)
+    }
+
+    pub(crate) fn edges(&self, state: StateId) -> &[DefaultEdge<A>] {
+        self.edges.get(&state).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (StateId, &DefaultEdge<A>)> + '_ {
+        self.edges
+            .iter()
+            .flat_map(|(&from, edges)| edges.iter().map(move |edge| (from, edge)))
+    }
+
+    pub(crate) fn insert(&mut self, from: StateId, edge: DefaultEdge<A>) {
+        self.edges.entry(from).or_default().push(edge);
+    }
+
+    // Where the default edges of `state` lead on `c`.
+    pub(crate) fn targets(&self, state: StateId, c: A) -> impl Iterator<Item = StateId> + '_ {
+        let in_domain = self.contains(c);
+        self.edges(state)
+            .iter()
+            .filter(move |edge| in_domain && edge.except.binary_search(&c).is_err())
+            .map(|edge| edge.to)
+    }
+
+    // Whether the domain has a symbol besides the sorted `symbols`.
+    pub(crate) fn has_other_symbol(&self, symbols: &[A]) -> bool {
+        self.domain
+            .iter()
+            .flat_map(range_symbols)
+            .any(|c| symbols.binary_search(&c).is_err())
+    }
+
+    // Every edge as (from, symbol, to), once for each symbol it is taken on.
+    pub(crate) fn symbol_edges(&self) -> impl Iterator<Item = (StateId, A, StateId)> + '_ {
+        self.iter().flat_map(move |(from, edge)| {
+            self.domain
+                .iter()
+                .flat_map(range_symbols)
+                .filter(move |c| edge.except.binary_search(c).is_err())
+                .map(move |c| (from, c, edge.to))
+        })
+    }
+}
+
+// Two iterators of edges sorted by symbol merged into one.
+struct Merge<L: Iterator, R: Iterator> {
+    left: Peekable<L>,
+    right: Peekable<R>,
+}
+
+impl<A: Symbol, L, R> Iterator for Merge<L, R>
+where
+    L: Iterator<Item = (A, StateId)>,
+    R: Iterator<Item = (A, StateId)>,
+{
+    type Item = (A, StateId);
+
+    fn next(&mut self) -> Option<(A, StateId)> {
+        match (self.left.peek(), self.right.peek()) {
+            (Some(left), Some(right)) if right.0 < left.0 => self.right.next(),
+            (Some(_), _) => self.left.next(),
+            (None, _) => self.right.next(),
+        }
+    }
+}
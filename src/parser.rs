@@ -1,21 +1,57 @@
-use std::{iter::Peekable, ops::RangeInclusive, str::Chars};
+use std::{iter::Peekable, ops::RangeInclusive, str::Chars, sync::OnceLock};
 
-use crate::{
-    StatePair,
-    epsilon_nfa::{EpsilonNfa, EpsilonNfaBuilder},
-};
+use crate::{ast::Ast, search::Anchors};
 
 pub struct Parser<'a> {
     pattern_iter: Peekable<Chars<'a>>,
+    case_insensitive: bool,
+    dot_matches_new_line: bool,
+    multi_line: bool,
+    unicode: bool,
+    nest_limit: Option<usize>,
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(pattern: &'a str) -> Self {
         Parser {
             pattern_iter: pattern.chars().peekable(),
+            case_insensitive: false,
+            dot_matches_new_line: true,
+            multi_line: false,
+            unicode: false,
+            nest_limit: None,
+            depth: 0,
         }
     }
 
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn dot_matches_new_line(mut self, dot_matches_new_line: bool) -> Self {
+        self.dot_matches_new_line = dot_matches_new_line;
+        self
+    }
+
+    pub fn multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    // Without Unicode, `.`, negated classes and the Perl classes only cover
+    // the chars up to U+00FF.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    pub fn nest_limit(mut self, nest_limit: usize) -> Self {
+        self.nest_limit = Some(nest_limit);
+        self
+    }
+
     fn parser_peek(&mut self) -> Option<char> {
         self.pattern_iter.peek().copied()
     }
@@ -53,69 +89,52 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_re(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
-        match self.parse_simple_re(epsilon_nfa_builder)? {
-            Some(simple_re_res) => Ok(Some(
-                self.parse_re_tail(epsilon_nfa_builder, simple_re_res)?,
-            )),
+    fn parse_re(&mut self) -> Result<Option<Ast>, String> {
+        match self.parse_simple_re()? {
+            Some(simple_re_res) => {
+                let mut alternatives = vec![simple_re_res];
+                self.parse_re_tail(&mut alternatives)?;
+                Ok(Some(Ast::alternation(alternatives)))
+            }
             None => Ok(None),
         }
     }
 
-    fn parse_re_tail(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-        lvalue: StatePair,
-    ) -> Result<StatePair, String> {
+    fn parse_re_tail(&mut self, alternatives: &mut Vec<Ast>) -> Result<(), String> {
         match self.parser_peek() {
             Some('|') => {
                 self.parser_match('|')?;
 
-                let Some(simple_re_res) = self.parse_simple_re(epsilon_nfa_builder)? else {
+                let Some(simple_re_res) = self.parse_simple_re()? else {
                     return Err("Unexpectedly found no simple_re after `|`".to_string());
                 };
 
-                let union_res = epsilon_nfa_builder.add_union_transition(lvalue, simple_re_res);
-                self.parse_re_tail(epsilon_nfa_builder, union_res)
+                alternatives.push(simple_re_res);
+                self.parse_re_tail(alternatives)
             }
-            _ => Ok(lvalue),
+            _ => Ok(()),
         }
     }
 
-    fn parse_simple_re(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
-        match self.parse_basic_re(epsilon_nfa_builder)? {
-            Some(simple_re_res) => Ok(Some(
-                self.parse_simple_re_tail(epsilon_nfa_builder, simple_re_res)?,
-            )),
+    fn parse_simple_re(&mut self) -> Result<Option<Ast>, String> {
+        match self.parse_basic_re()? {
+            Some(simple_re_res) => Ok(Some(self.parse_simple_re_tail(simple_re_res)?)),
             None => Ok(None),
         }
     }
 
-    fn parse_simple_re_tail(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-        lvalue: StatePair,
-    ) -> Result<StatePair, String> {
-        match self.parse_basic_re(epsilon_nfa_builder)? {
+    fn parse_simple_re_tail(&mut self, lvalue: Ast) -> Result<Ast, String> {
+        match self.parse_basic_re()? {
             Some(basic_re_res) => {
-                let concat_res = epsilon_nfa_builder.add_concat_transition(lvalue, basic_re_res);
-                self.parse_simple_re_tail(epsilon_nfa_builder, concat_res)
+                let concat_res = Ast::concat(lvalue, basic_re_res);
+                self.parse_simple_re_tail(concat_res)
             }
             None => Ok(lvalue),
         }
     }
 
-    fn parse_basic_re(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
-        let Some(elementary_re_res) = self.parse_elementary_re(epsilon_nfa_builder)? else {
+    fn parse_basic_re(&mut self) -> Result<Option<Ast>, String> {
+        let Some(elementary_re_res) = self.parse_elementary_re()? else {
             return Ok(None);
         };
 
@@ -123,19 +142,19 @@ impl<'a> Parser<'a> {
             Some('*') => {
                 self.parser_match('*')?;
 
-                let star_res = epsilon_nfa_builder.add_star_transition(elementary_re_res);
+                let star_res = Ast::repeat(elementary_re_res, 0, None);
                 Ok(Some(star_res))
             }
             Some('+') => {
                 self.parser_match('+')?;
 
-                let plus_res = epsilon_nfa_builder.add_plus_transition(elementary_re_res);
+                let plus_res = Ast::repeat(elementary_re_res, 1, None);
                 Ok(Some(plus_res))
             }
             Some('?') => {
                 self.parser_match('?')?;
 
-                let question_res = epsilon_nfa_builder.add_question_transition(elementary_re_res);
+                let question_res = Ast::repeat(elementary_re_res, 0, Some(1));
                 Ok(Some(question_res))
             }
             Some('{') => {
@@ -150,7 +169,10 @@ impl<'a> Parser<'a> {
                         .parser_match_one_of(digits)?
                         .to_digit(10)
                         .expect("c must be one of the digits");
-                    n = n * 10 + c as i32;
+                    n = n
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(c as i32))
+                        .ok_or("Counted repetition bound is too large")?;
                 }
 
                 match self.parser_peek() {
@@ -164,7 +186,10 @@ impl<'a> Parser<'a> {
                                         .parser_match_one_of(digits)?
                                         .to_digit(10)
                                         .expect("c must be one of the digits");
-                                    m = m * 10 + c as i32;
+                                    m = m
+                                        .checked_mul(10)
+                                        .and_then(|m| m.checked_add(c as i32))
+                                        .ok_or("Counted repetition bound is too large")?;
                                 }
                             }
                             _ => {
@@ -183,141 +208,95 @@ impl<'a> Parser<'a> {
                     return Err("Out of order range found in the pattern".to_string());
                 }
 
-                let start = epsilon_nfa_builder.add_state();
-                let end = epsilon_nfa_builder.add_state();
-
-                let (elementary_re_start, elementary_re_end) = elementary_re_res;
-
-                if n == 0 {
-                    epsilon_nfa_builder.add_epsilon_transition(start, end);
-                }
-
-                let mut repeated_elementary_re: Option<StatePair> = None;
-
-                for _ in 1..=n {
-                    let elementary_re_copy = epsilon_nfa_builder
-                        .make_deep_copy(elementary_re_start, elementary_re_end)?;
-
-                    if let Some(repeated_elementary_re_unwrapped) = repeated_elementary_re {
-                        repeated_elementary_re = Some(epsilon_nfa_builder.add_concat_transition(
-                            repeated_elementary_re_unwrapped,
-                            elementary_re_copy,
-                        ));
-                    } else {
-                        repeated_elementary_re = Some(elementary_re_copy);
-                    }
-                }
-
-                if m == -1 {
-                    let elementary_re_copy = epsilon_nfa_builder
-                        .make_deep_copy(elementary_re_start, elementary_re_end)?;
-
-                    let elementary_re_copy_star =
-                        epsilon_nfa_builder.add_star_transition(elementary_re_copy);
-                    if let Some(repeated_elementary_re_unwrapped) = repeated_elementary_re {
-                        repeated_elementary_re = Some(epsilon_nfa_builder.add_concat_transition(
-                            repeated_elementary_re_unwrapped,
-                            elementary_re_copy_star,
-                        ));
-                    } else {
-                        repeated_elementary_re = Some(elementary_re_copy_star);
-                    }
-                }
-
-                for _ in n + 1..=m {
-                    let elementary_re_copy = epsilon_nfa_builder
-                        .make_deep_copy(elementary_re_start, elementary_re_end)?;
-
-                    let elementary_re_copy_question =
-                        epsilon_nfa_builder.add_question_transition(elementary_re_copy);
-                    if let Some(repeated_elementary_re_unwrapped) = repeated_elementary_re {
-                        repeated_elementary_re = Some(epsilon_nfa_builder.add_concat_transition(
-                            repeated_elementary_re_unwrapped,
-                            elementary_re_copy_question,
-                        ));
-                    } else {
-                        repeated_elementary_re = Some(elementary_re_copy_question);
-                    }
-                }
+                let min = n as usize;
+                let max = (m != -1).then_some(m as usize);
 
-                if let Some((repeated_elementary_re_start, repeated_elementary_re_end)) =
-                    repeated_elementary_re
-                {
-                    epsilon_nfa_builder.add_epsilon_transition(start, repeated_elementary_re_start);
-                    epsilon_nfa_builder.add_epsilon_transition(repeated_elementary_re_end, end);
-                }
-
-                Ok(Some((start, end)))
+                Ok(Some(Ast::repeat(elementary_re_res, min, max)))
             }
             _ => Ok(Some(elementary_re_res)),
         }
     }
 
-    fn parse_elementary_re(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
-        if let Some(group_res) = self.parse_group(epsilon_nfa_builder)? {
+    fn parse_elementary_re(&mut self) -> Result<Option<Ast>, String> {
+        if let Some(group_res) = self.parse_group()? {
             return Ok(Some(group_res));
         }
 
-        if let Some(any_res) = self.parse_any(epsilon_nfa_builder)? {
+        if let Some(any_res) = self.parse_any()? {
             return Ok(Some(any_res));
         }
 
-        if let Some(char_res) = self.parse_char(epsilon_nfa_builder)? {
+        if let Some(char_res) = self.parse_char()? {
             return Ok(Some(char_res));
         }
 
-        if let Some(set_res) = self.parse_set(epsilon_nfa_builder)? {
+        if let Some(set_res) = self.parse_set()? {
             return Ok(Some(set_res));
         }
 
         Ok(None)
     }
 
-    fn parse_group(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
+    fn parse_group(&mut self) -> Result<Option<Ast>, String> {
         match self.parser_peek() {
             Some('(') => {
                 self.parser_match('(')?;
-                let re_res = self.parse_re(epsilon_nfa_builder)?;
+                self.depth += 1;
+                if let Some(nest_limit) = self.nest_limit
+                    && self.depth > nest_limit
+                {
+                    return Err(format!(
+                        "Groups are nested deeper than the limit of {nest_limit}"
+                    ));
+                }
+                let name = self.parse_group_name()?;
+                let re_res = self.parse_re()?;
                 self.parser_match(')')?;
-                Ok(re_res)
+                self.depth -= 1;
+                match name {
+                    Some(name) => Ok(Some(Ast::capture(re_res.unwrap_or(Ast::Empty), &name))),
+                    None => Ok(re_res),
+                }
             }
             _ => Ok(None),
         }
     }
 
-    fn parse_any(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
+    // The `?<name>` opening a named group.
+    fn parse_group_name(&mut self) -> Result<Option<String>, String> {
+        if self.pattern_iter.next_if_eq(&'?').is_none() {
+            return Ok(None);
+        }
+        self.parser_match('<')?;
+        let mut name = String::new();
+        while let Some(c) = self.pattern_iter.next_if(|&c| is_word(c)) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            return Err("Expected a group name".to_string());
+        }
+        self.parser_match('>')?;
+        Ok(Some(name))
+    }
+
+    fn parse_any(&mut self) -> Result<Option<Ast>, String> {
         match self.parser_peek() {
             Some('.') => {
                 self.parser_match('.')?;
 
-                let start = epsilon_nfa_builder.add_state();
-                let end = epsilon_nfa_builder.add_state();
-
-                epsilon_nfa_builder.add_transition_range(
-                    start,
-                    u8::MIN as char..=u8::MAX as char,
-                    end,
-                );
+                let mut ranges = vec![self.universe()];
+                if !self.dot_matches_new_line {
+                    ranges = complement(&['\n'..='\n'], &ranges);
+                }
+                let ast = Ast::class_ranges(ranges);
 
-                Ok(Some((start, end)))
+                Ok(Some(ast))
             }
             _ => Ok(None),
         }
     }
 
-    fn parse_char(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
+    fn parse_char(&mut self) -> Result<Option<Ast>, String> {
         let meta_characters = "[]\\.^$*+?{}|()";
         let possible_escape_characters = "[]\\.^$*+?{}|()wWsSdDnrt";
         let white_space = "\t\n\r ";
@@ -326,105 +305,85 @@ impl<'a> Parser<'a> {
                 self.parser_match('\\')?;
                 match self.parser_match_one_of(possible_escape_characters)? {
                     c if meta_characters.contains(c) => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-                        epsilon_nfa_builder.add_transition(start, c, end);
+                        let ast = self.literal(c);
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
+                    }
+                    'w' if self.unicode => Ok(Some(Ast::class_ranges(unicode_words().to_vec()))),
+                    'W' if self.unicode => {
+                        Ok(Some(Ast::class_ranges(self.negate(unicode_words()))))
+                    }
+                    's' if self.unicode => {
+                        Ok(Some(Ast::class_ranges(unicode_white_space().to_vec())))
+                    }
+                    'S' if self.unicode => {
+                        Ok(Some(Ast::class_ranges(self.negate(unicode_white_space()))))
+                    }
+                    'd' if self.unicode => Ok(Some(Ast::class_ranges(unicode_digits().to_vec()))),
+                    'D' if self.unicode => {
+                        Ok(Some(Ast::class_ranges(self.negate(unicode_digits()))))
                     }
                     'w' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-
-                        epsilon_nfa_builder.add_transition_range(
-                            start,
+                        let ast = Ast::class(
                             ('a'..='z')
                                 .chain('A'..='Z')
                                 .chain('0'..='9')
                                 .chain('_'..='_'),
-                            end,
                         );
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     'W' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-
-                        epsilon_nfa_builder.add_transition_range(
-                            start,
+                        let ast = Ast::class(
                             (u8::MIN..=u8::MAX)
                                 .map(|c| c as char)
                                 .filter(|c| !c.is_alphanumeric() && *c != '_'),
-                            end,
                         );
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     's' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-
-                        epsilon_nfa_builder.add_transition_range(start, white_space.chars(), end);
+                        let ast = Ast::class(white_space.chars());
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     'S' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-
-                        epsilon_nfa_builder.add_transition_range(
-                            start,
+                        let ast = Ast::class(
                             (u8::MIN..=u8::MAX)
                                 .map(|c| c as char)
                                 .filter(|&c| !white_space.contains(c)),
-                            end,
                         );
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     'd' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-
-                        epsilon_nfa_builder.add_transition_range(start, '0'..='9', end);
+                        let ast = Ast::class('0'..='9');
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     'D' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-
-                        epsilon_nfa_builder.add_transition_range(
-                            start,
+                        let ast = Ast::class(
                             (u8::MIN..=u8::MAX)
                                 .map(|c| c as char)
                                 .filter(|c| !c.is_ascii_digit()),
-                            end,
                         );
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     'n' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-                        epsilon_nfa_builder.add_transition(start, '\n', end);
+                        let ast = Ast::literal('\n');
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     'r' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-                        epsilon_nfa_builder.add_transition(start, '\r', end);
+                        let ast = Ast::literal('\r');
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     't' => {
-                        let start = epsilon_nfa_builder.add_state();
-                        let end = epsilon_nfa_builder.add_state();
-                        epsilon_nfa_builder.add_transition(start, '\t', end);
+                        let ast = Ast::literal('\t');
 
-                        Ok(Some((start, end)))
+                        Ok(Some(ast))
                     }
                     _ => Err("Unexpected behaviour in parse_char()".to_string()),
                 }
@@ -436,20 +395,15 @@ impl<'a> Parser<'a> {
 
                 let c = self.parser_match_none_of(meta_characters)?;
 
-                let start = epsilon_nfa_builder.add_state();
-                let end = epsilon_nfa_builder.add_state();
-                epsilon_nfa_builder.add_transition(start, c, end);
+                let ast = self.literal(c);
 
-                Ok(Some((start, end)))
+                Ok(Some(ast))
             }
             None => Ok(None),
         }
     }
 
-    fn parse_set(
-        &mut self,
-        epsilon_nfa_builder: &mut EpsilonNfaBuilder,
-    ) -> Result<Option<StatePair>, String> {
+    fn parse_set(&mut self) -> Result<Option<Ast>, String> {
         match self.parser_peek() {
             Some('[') => {
                 self.parser_match('[')?;
@@ -467,26 +421,18 @@ impl<'a> Parser<'a> {
 
                 self.parser_match(']')?;
 
-                let start = epsilon_nfa_builder.add_state();
-                let end = epsilon_nfa_builder.add_state();
-
-                if negate {
-                    epsilon_nfa_builder.add_transition_range(
-                        start,
-                        (u8::MIN..=u8::MAX)
-                            .map(|c| c as char)
-                            .filter(|&c| !range.iter().any(|r| r.contains(&c))),
-                        end,
-                    );
+                let range = if self.case_insensitive {
+                    fold_case(&range)
                 } else {
-                    epsilon_nfa_builder.add_transition_range(
-                        start,
-                        range.into_iter().flatten(),
-                        end,
-                    );
-                }
+                    range
+                };
+                let ast = if negate {
+                    Ast::class_ranges(self.negate(&range))
+                } else {
+                    Ast::class_ranges(range)
+                };
 
-                Ok(Some((start, end)))
+                Ok(Some(ast))
             }
             _ => Ok(None),
         }
@@ -565,16 +511,171 @@ impl<'a> Parser<'a> {
         }
     }
 
-    pub fn parse(&mut self) -> Result<EpsilonNfa, String> {
-        let mut epsilon_nfa_builder = EpsilonNfaBuilder::new();
+    fn literal(&self, c: char) -> Ast {
+        if self.case_insensitive {
+            Ast::class(case_variants(c))
+        } else {
+            Ast::literal(c)
+        }
+    }
+
+    fn universe(&self) -> RangeInclusive<char> {
+        if self.unicode {
+            '\0'..=char::MAX
+        } else {
+            '\0'..=u8::MAX as char
+        }
+    }
+
+    fn negate(&self, ranges: &[RangeInclusive<char>]) -> Vec<RangeInclusive<char>> {
+        complement(ranges, &[self.universe()])
+    }
+
+    // Parses a pattern whose alternatives may start with `^` and end with `$`,
+    // which are not supported anywhere else. Either anchor has to be on every
+    // alternative or on none of them.
+    pub fn parse_anchored(&mut self) -> Result<(Ast, Anchors), String> {
+        let mut alternatives: Vec<Ast> = Vec::new();
+        let mut starts: Vec<bool> = Vec::new();
+        let mut ends: Vec<bool> = Vec::new();
+        loop {
+            let start = self.pattern_iter.next_if_eq(&'^').is_some();
+            let ast = self.parse_simple_re()?;
+            let end = self.pattern_iter.next_if_eq(&'$').is_some();
+            match ast {
+                Some(ast) => alternatives.push(ast),
+                None if start || end => alternatives.push(Ast::Empty),
+                None if alternatives.is_empty() => {
+                    return Err("Could not generate epsilon nfa".to_string());
+                }
+                None => return Err("Unexpectedly found no simple_re after `|`".to_string()),
+            }
+            starts.push(start);
+            ends.push(end);
+
+            if self.pattern_iter.next_if_eq(&'|').is_none() {
+                break;
+            }
+        }
+        if let Some(c) = self.parser_peek() {
+            return Err(format!("Unexpected {c:?} found in the pattern"));
+        }
+        if starts.contains(&!starts[0]) || ends.contains(&!ends[0]) {
+            return Err("`^` and `$` must anchor every alternative or none of them".to_string());
+        }
+
+        let anchors = Anchors {
+            start: starts[0],
+            end: ends[0],
+            multi_line: self.multi_line,
+        };
+        Ok((Ast::alternation(alternatives), anchors))
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// The zero of every run of ten decimal digits, the chars of general category
+// Nd. Each run goes on from its zero to its nine.
+const DECIMAL_DIGIT_ZEROS: [u32; 77] = [
+    0x30, 0x660, 0x6F0, 0x7C0, 0x966, 0x9E6, 0xA66, 0xAE6, 0xB66, 0xBE6, 0xC66, 0xCE6, 0xD66,
+    0xDE6, 0xE50, 0xED0, 0xF20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80, 0x1A90,
+    0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0, 0xFF10,
+    0x104A0, 0x10D30, 0x10D40, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x116D0, 0x116DA, 0x11730, 0x118E0, 0x11950, 0x11BF0, 0x11C50, 0x11D50,
+    0x11DA0, 0x11DE0, 0x11F50, 0x16130, 0x16A60, 0x16AC0, 0x16B50, 0x16D70, 0x1CCF0, 0x1D7CE,
+    0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E4F0, 0x1E5F1, 0x1E950, 0x1FBF0,
+];
+
+// The Unicode classes, built on first use rather than on every parse.
+fn unicode_digits() -> &'static [RangeInclusive<char>] {
+    static DIGITS: OnceLock<Vec<RangeInclusive<char>>> = OnceLock::new();
+    DIGITS.get_or_init(|| {
+        DECIMAL_DIGIT_ZEROS
+            .iter()
+            .filter_map(|&zero| char_range(zero, zero + 9))
+            .collect()
+    })
+}
+
+fn unicode_words() -> &'static [RangeInclusive<char>] {
+    static WORDS: OnceLock<Vec<RangeInclusive<char>>> = OnceLock::new();
+    WORDS.get_or_init(|| unicode_class(is_word))
+}
+
+fn unicode_white_space() -> &'static [RangeInclusive<char>] {
+    static WHITE_SPACE: OnceLock<Vec<RangeInclusive<char>>> = OnceLock::new();
+    WHITE_SPACE.get_or_init(|| unicode_class(char::is_whitespace))
+}
+
+// The ranges of all chars with the property.
+fn unicode_class(property: fn(char) -> bool) -> Vec<RangeInclusive<char>> {
+    let mut ranges: Vec<RangeInclusive<char>> = Vec::new();
+    for c in ('\0'..=char::MAX).filter(|&c| property(c)) {
+        match ranges.last_mut() {
+            Some(range) if *range.end() as u32 + 1 == c as u32 => *range = *range.start()..=c,
+            _ => ranges.push(c..=c),
+        }
+    }
+    ranges
+}
 
-        match self.parse_re(&mut epsilon_nfa_builder)? {
-            Some((epsilon_nfa_start, epsilon_nfa_end)) => Ok(EpsilonNfa::new(
-                epsilon_nfa_builder.transitions,
-                epsilon_nfa_start,
-                epsilon_nfa_end,
-            )),
-            None => Err("Could not generate epsilon nfa".to_string()),
+// The chars of `universe` outside of `ranges`.
+fn complement(
+    ranges: &[RangeInclusive<char>],
+    universe: &[RangeInclusive<char>],
+) -> Vec<RangeInclusive<char>> {
+    let Ast::Class(ranges) = Ast::class_ranges(ranges.iter().cloned()) else {
+        unreachable!("class_ranges always builds a class");
+    };
+
+    let mut complement: Vec<RangeInclusive<char>> = Vec::new();
+    for outer in universe {
+        let mut low = *outer.start() as u32;
+        for range in &ranges {
+            let (start, end) = (*range.start() as u32, *range.end() as u32);
+            if end < low || start > *outer.end() as u32 {
+                continue;
+            }
+            if start > low {
+                complement.extend(char_range(low, start - 1));
+            }
+            low = end + 1;
+        }
+        if low <= *outer.end() as u32 {
+            complement.extend(char_range(low, *outer.end() as u32));
         }
     }
+    complement
+}
+
+// The chars from low to high, or None if there are none, stepping around the
+// surrogates that are not chars.
+fn char_range(low: u32, high: u32) -> Option<RangeInclusive<char>> {
+    let low = (low..=high).find_map(char::from_u32)?;
+    let high = (low as u32..=high).rev().find_map(char::from_u32)?;
+    Some(low..=high)
+}
+
+// The char and its lowercase and uppercase forms, leaving out mappings to more
+// than one char.
+fn case_variants(c: char) -> impl Iterator<Item = char> {
+    let lower = single_char(c.to_lowercase());
+    let upper = single_char(c.to_uppercase());
+    [Some(c), lower, upper].into_iter().flatten()
+}
+
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    let first = chars.next()?;
+    chars.next().is_none().then_some(first)
+}
+
+fn fold_case(ranges: &[RangeInclusive<char>]) -> Vec<RangeInclusive<char>> {
+    let Ast::Class(folded) = Ast::class(ranges.iter().cloned().flatten().flat_map(case_variants))
+    else {
+        unreachable!("class always builds a class");
+    };
+    folded
 }
@@ -0,0 +1,129 @@
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
+use crate::{ast::Ast, symbol::Symbol};
+
+// The chars the parser reads as syntax outside of a set.
+const META_CHARACTERS: &str = "[]\\.^$*+?{}|()";
+
+// The pattern matching exactly `s`, built the way the parser builds a run of
+// plain chars.
+pub fn lit<A: Symbol, S: IntoSymbols<A>>(s: S) -> Ast<A> {
+    s.into_symbols()
+        .into_iter()
+        .map(Ast::literal)
+        .reduce(Ast::concat)
+        .unwrap_or(Ast::Empty)
+}
+
+// What lit takes: the chars of a string, or the symbols of a slice or array.
+pub trait IntoSymbols<A> {
+    fn into_symbols(self) -> Vec<A>;
+}
+
+impl IntoSymbols<char> for &str {
+    fn into_symbols(self) -> Vec<char> {
+        self.chars().collect()
+    }
+}
+
+impl<A: Symbol> IntoSymbols<A> for &[A] {
+    fn into_symbols(self) -> Vec<A> {
+        self.to_vec()
+    }
+}
+
+impl<A: Symbol, const N: usize> IntoSymbols<A> for [A; N] {
+    fn into_symbols(self) -> Vec<A> {
+        self.to_vec()
+    }
+}
+
+impl<A: Symbol> IntoSymbols<A> for Vec<A> {
+    fn into_symbols(self) -> Vec<A> {
+        self
+    }
+}
+
+// A set of symbols, as `[...]` would give.
+pub fn class<A: Symbol, I: IntoIterator<Item = RangeInclusive<A>>>(ranges: I) -> Ast<A> {
+    Ast::class_ranges(ranges)
+}
+
+// Puts a backslash before every char the parser would otherwise take as
+// syntax, so `s` can be spliced into a pattern string and match itself.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if META_CHARACTERS.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// The postfix operators of the pattern syntax, as methods.
+pub trait Pattern: Sized {
+    // `{n}`, `{n,}` and `{n,m}` in range form: `2..=5`, `3..`, `..4`. Panics
+    // on a range no count is in, like `5..3` or `..0`.
+    fn repeat<R: RangeBounds<usize>>(self, range: R) -> Self;
+
+    // `?`
+    fn optional(self) -> Self;
+
+    // `(?<name>...)`
+    fn capture(self, name: &str) -> Self;
+}
+
+impl<A: Symbol> Pattern for Ast<A> {
+    fn repeat<R: RangeBounds<usize>>(self, range: R) -> Self {
+        let min = match range.start_bound() {
+            Bound::Included(&min) => min,
+            Bound::Excluded(&min) => min
+                .checked_add(1)
+                .expect("repeat range starts after the largest count"),
+            Bound::Unbounded => 0,
+        };
+        let max = match range.end_bound() {
+            Bound::Included(&max) => Some(max),
+            Bound::Excluded(&max) => Some(
+                max.checked_sub(1)
+                    .expect("repeat range ends before the smallest count"),
+            ),
+            Bound::Unbounded => None,
+        };
+        if let Some(max) = max {
+            assert!(min <= max, "repeat range starts at {min} but ends at {max}");
+        }
+        Ast::repeat(self, min, max)
+    }
+
+    fn optional(self) -> Self {
+        Ast::repeat(self, 0, Some(1))
+    }
+
+    fn capture(self, name: &str) -> Self {
+        Ast::capture(self, name)
+    }
+}
+
+// The patterns one after another, like writing them side by side.
+#[macro_export]
+macro_rules! seq {
+    () => {
+        $crate::regex::Ast::Empty
+    };
+    ($first:expr $(, $rest:expr)* $(,)?) => {{
+        let ast: $crate::regex::Ast<_> = $first;
+        $(let ast = $crate::regex::Ast::concat(ast, $rest);)*
+        ast
+    }};
+}
+
+// Any one of the patterns, like joining them with `|`.
+#[macro_export]
+macro_rules! alt {
+    ($($alternative:expr),* $(,)?) => {
+        $crate::regex::Ast::alternation(::std::vec![$($alternative),*])
+    };
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashSet, mem};
+
+use crate::{
+    StateId,
+    epsilon_nfa::{EpsilonNfa, State},
+    search::{Anchors, Input, MatchKind, into_ok},
+};
+
+#[derive(Debug, Clone, Copy)]
+struct Thread {
+    state: StateId,
+    // Repetitions done so far, for counted states.
+    count: usize,
+    // Where the match this thread is trying started.
+    start: usize,
+}
+
+// Threads in priority order. A thread reaching a state and count already taken
+// is dropped, since the earlier one has the same future and a better claim.
+#[derive(Debug, Default)]
+struct Threads {
+    list: Vec<Thread>,
+    seen: HashSet<(StateId, usize)>,
+}
+
+impl Threads {
+    fn push(&mut self, thread: Thread) {
+        if self.seen.insert((thread.state, thread.count)) {
+            self.list.push(thread);
+        }
+    }
+
+    fn enter(&mut self, nfa: &EpsilonNfa, state: StateId, start: usize) {
+        for &epsilon_state in nfa.epsilon_closure(state) {
+            self.push(Thread {
+                state: epsilon_state,
+                count: 0,
+                start,
+            });
+        }
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.seen.clear();
+    }
+}
+
+// Runs every match attempt from `start` on in lockstep, one thread per state of
+// the εNFA. Epsilon closures list their states in the order a backtracking
+// engine would try them, and the threads of earlier attempts come first, so
+// the first thread to reach the match state is the leftmost-first match.
+pub(crate) fn find_at(
+    nfa: &EpsilonNfa,
+    haystack: &str,
+    start: usize,
+    match_kind: MatchKind,
+    anchors: &Anchors,
+) -> Option<(usize, usize)> {
+    into_ok(find_in(nfa, &mut { haystack }, start, match_kind, anchors))
+}
+
+pub(crate) fn find_in<I: Input + ?Sized>(
+    nfa: &EpsilonNfa,
+    input: &mut I,
+    start: usize,
+    match_kind: MatchKind,
+    anchors: &Anchors,
+) -> Result<Option<(usize, usize)>, I::Error> {
+    let mut current = Threads::default();
+    let mut next = Threads::default();
+    let mut best: Option<(usize, usize)> = None;
+    let mut at = start;
+    loop {
+        // Attempts starting after a match was found can never win.
+        if best.is_none() && anchors.is_start_allowed(input, at) {
+            current.enter(nfa, nfa.start(), at);
+        }
+        input.keep_from(current.list.first().map_or(at, |thread| thread.start));
+
+        let c = input.char_at(at)?;
+        let is_end_allowed = anchors.is_end_allowed(input, at)?;
+        for &thread in &current.list {
+            match nfa.state(thread.state) {
+                State::Match if is_end_allowed => {
+                    // Earlier attempts come first, so this is the leftmost of
+                    // the matches ending here.
+                    if match_kind == MatchKind::Earliest {
+                        return Ok(Some((thread.start, at)));
+                    }
+                    let is_better = match (match_kind, best) {
+                        (_, None) => true,
+                        // Threads still running outrank the ones that matched earlier.
+                        (MatchKind::LeftmostFirst, Some(_)) => true,
+                        (
+                            MatchKind::LeftmostLongest | MatchKind::Earliest,
+                            Some((best_start, best_end)),
+                        ) => {
+                            thread.start < best_start
+                                || (thread.start == best_start && at > best_end)
+                        }
+                    };
+                    if is_better {
+                        best = Some((thread.start, at));
+                    }
+                    if match_kind == MatchKind::LeftmostFirst {
+                        break;
+                    }
+                }
+                State::Range {
+                    ranges,
+                    next: next_state,
+                } => {
+                    if let Some(c) = c
+                        && ranges.iter().any(|range| range.contains(&c))
+                    {
+                        next.enter(nfa, *next_state, thread.start);
+                    }
+                }
+                State::Counted {
+                    ranges,
+                    min,
+                    next: next_state,
+                    ..
+                } => {
+                    if let Some(c) = c
+                        && ranges.iter().any(|range| range.contains(&c))
+                        && let Some(count) = nfa.next_count(thread.state, thread.count)
+                    {
+                        // Greedy: another repetition is tried before leaving.
+                        next.push(Thread { count, ..thread });
+                        if count >= *min {
+                            next.enter(nfa, *next_state, thread.start);
+                        }
+                    }
+                }
+                State::Match | State::Split { .. } | State::Epsilon { .. } => {}
+            }
+        }
+
+        if let Some((best_start, _)) = best {
+            match match_kind {
+                MatchKind::LeftmostFirst => {}
+                MatchKind::LeftmostLongest | MatchKind::Earliest => {
+                    next.list.retain(|thread| thread.start <= best_start)
+                }
+            }
+        }
+
+        let Some(c) = c else {
+            break;
+        };
+        at += c.len_utf8();
+        mem::swap(&mut current, &mut next);
+        next.clear();
+        // No attempt is running and no later one can start or win.
+        if current.list.is_empty() && (best.is_some() || (anchors.start && !anchors.multi_line)) {
+            break;
+        }
+    }
+
+    Ok(best)
+}
@@ -0,0 +1,55 @@
+use crate::{ast::Ast, literal::Horspool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefilterKind {
+    // Every match starts with the literal, so only its occurrences need to be
+    // tried as match starts.
+    Prefix,
+    // Every match contains the literal somewhere, so a search can stop as soon
+    // as no occurrence is left at or after the current position.
+    Inner,
+}
+
+#[derive(Debug, Clone)]
+pub struct Prefilter {
+    searcher: Horspool,
+    kind: PrefilterKind,
+}
+
+impl Prefilter {
+    pub fn new(ast: &Ast) -> Option<Self> {
+        let (prefix, _) = ast.literal_prefix();
+        if !prefix.is_empty() {
+            return Some(Self {
+                searcher: Horspool::new(&prefix),
+                kind: PrefilterKind::Prefix,
+            });
+        }
+
+        let required = ast.required_literal();
+        if !required.is_empty() {
+            return Some(Self {
+                searcher: Horspool::new(&required),
+                kind: PrefilterKind::Inner,
+            });
+        }
+
+        None
+    }
+
+    pub fn literal(&self) -> &str {
+        self.searcher.needle()
+    }
+
+    pub fn kind(&self) -> PrefilterKind {
+        self.kind
+    }
+
+    pub(crate) fn find_at(&self, haystack: &str, start: usize) -> Option<usize> {
+        self.searcher.find_at(haystack, start)
+    }
+
+    pub(crate) fn find_bytes_at(&self, haystack: &[u8], start: usize) -> Option<usize> {
+        self.searcher.find_bytes_at(haystack, start)
+    }
+}
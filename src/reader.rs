@@ -0,0 +1,342 @@
+use std::{
+    io::{self, BufRead, ErrorKind, Read},
+    mem,
+    ops::Range,
+    str,
+};
+
+use crate::{
+    engine::Engine,
+    prefilter::Prefilter,
+    search::{Anchors, Input, Searcher},
+    stream::utf8_width,
+};
+
+const READ_CHUNK_LEN: usize = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReaderMatch {
+    start: u64,
+    end: u64,
+    bytes: Vec<u8>,
+}
+
+impl ReaderMatch {
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[derive(Debug)]
+pub struct ReaderFindIter<'e, R> {
+    engine: &'e Engine,
+    anchors: Anchors,
+    reader: R,
+    // buffer[0] is at offset `offset` in the input. The search no longer looks
+    // at the input before `kept`, so all but the byte right before it, which
+    // `^` needs, can be dropped.
+    buffer: Vec<u8>,
+    offset: usize,
+    kept: usize,
+    is_eof: bool,
+    // Where the next search starts, or None once the input is used up.
+    at: Option<usize>,
+    // Where the search ran into input that is not UTF-8.
+    invalid_at: Option<usize>,
+    last_match_end: Option<usize>,
+}
+
+impl<'e, R: Read> ReaderFindIter<'e, R> {
+    pub(crate) fn new(engine: &'e Engine, reader: R, anchors: Anchors) -> Self {
+        Self {
+            engine,
+            anchors,
+            reader,
+            buffer: Vec::new(),
+            offset: 0,
+            kept: 0,
+            is_eof: false,
+            at: Some(0),
+            invalid_at: None,
+            last_match_end: None,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let consumed = self.kept.saturating_sub(1).saturating_sub(self.offset);
+        if consumed >= READ_CHUNK_LEN {
+            self.buffer.drain(..consumed);
+            self.offset += consumed;
+        }
+
+        let len = self.buffer.len();
+        self.buffer.resize(len + READ_CHUNK_LEN, 0);
+        let read = loop {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Ok(read) => break read,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.buffer.truncate(len);
+                    return Err(error);
+                }
+            }
+        };
+        self.buffer.truncate(len + read);
+        self.is_eof = read == 0;
+        Ok(())
+    }
+
+    // Steps over the invalid UTF-8 at `at` as a whole, so that it is reported
+    // once, and returns the error reporting it.
+    fn skip_invalid(&mut self, at: usize) -> io::Error {
+        let from = at - self.offset;
+        let to = (from + 4).min(self.buffer.len());
+        let error =
+            str::from_utf8(&self.buffer[from..to]).expect_err("the search stops on invalid UTF-8");
+        self.at = Some(at + error.error_len().unwrap_or(to - from));
+        io::Error::new(ErrorKind::InvalidData, error)
+    }
+
+    // Invalid UTF-8 ends the search like the end of the input does. It is
+    // reported once no match is found before it.
+    fn try_next(&mut self) -> io::Result<Option<ReaderMatch>> {
+        loop {
+            let Some(at) = self.at else {
+                return Ok(None);
+            };
+            self.kept = at;
+            self.invalid_at = None;
+            let (engine, anchors) = (self.engine, self.anchors);
+            let Some((start, end)) = engine.find_in(self, at, &anchors)? else {
+                return match self.invalid_at {
+                    Some(invalid_at) => Err(self.skip_invalid(invalid_at)),
+                    None => {
+                        self.at = None;
+                        Ok(None)
+                    }
+                };
+            };
+
+            if start == end {
+                self.invalid_at = None;
+                self.at = match self.char_at(end)? {
+                    Some(c) => Some(end + c.len_utf8()),
+                    None => match self.invalid_at {
+                        Some(invalid_at) => return Err(self.skip_invalid(invalid_at)),
+                        None => None,
+                    },
+                };
+                // An empty match right where the previous match ended would
+                // report the same position twice, so it is skipped.
+                if Some(end) == self.last_match_end {
+                    continue;
+                }
+            } else {
+                self.at = Some(end);
+            }
+            self.last_match_end = Some(end);
+
+            return Ok(Some(ReaderMatch {
+                start: start as u64,
+                end: end as u64,
+                bytes: self.buffer[start - self.offset..end - self.offset].to_vec(),
+            }));
+        }
+    }
+}
+
+// Reads more input whenever the search gets to the end of the buffer.
+impl<R: Read> Input for ReaderFindIter<'_, R> {
+    type Error = io::Error;
+
+    fn char_at(&mut self, at: usize) -> io::Result<Option<char>> {
+        loop {
+            let from = at - self.offset;
+            if let Some(&lead) = self.buffer.get(from) {
+                let to = from + utf8_width(lead);
+                if to <= self.buffer.len() || self.is_eof {
+                    let to = to.min(self.buffer.len());
+                    return Ok(match str::from_utf8(&self.buffer[from..to]) {
+                        Ok(decoded) => decoded.chars().next(),
+                        Err(_) => {
+                            self.invalid_at = Some(at);
+                            None
+                        }
+                    });
+                }
+            } else if self.is_eof {
+                return Ok(None);
+            }
+            self.fill()?;
+        }
+    }
+
+    fn byte_at(&mut self, at: usize) -> io::Result<Option<u8>> {
+        loop {
+            if let Some(&b) = self.buffer.get(at - self.offset) {
+                return Ok(Some(b));
+            }
+            if self.is_eof {
+                return Ok(None);
+            }
+            self.fill()?;
+        }
+    }
+
+    fn byte_before(&self, at: usize) -> Option<u8> {
+        at.checked_sub(1).map(|at| self.buffer[at - self.offset])
+    }
+
+    fn buffered(&self, at: usize) -> &[u8] {
+        &self.buffer[at - self.offset..]
+    }
+
+    // Without an occurrence in the buffer, only one straddling its end is still
+    // possible, so the search can go on up to there before more is read. The
+    // scan stops on invalid UTF-8, so that the search gets to report it.
+    fn find_literal(&mut self, prefilter: &Prefilter, at: usize) -> io::Result<Option<usize>> {
+        loop {
+            let from = at - self.offset;
+            let found = prefilter.find_bytes_at(&self.buffer, from);
+            let to = match found {
+                Some(found) => found,
+                None if self.is_eof => self.buffer.len(),
+                None => self
+                    .buffer
+                    .len()
+                    .saturating_sub(prefilter.literal().len() - 1),
+            };
+            if found.is_some() || self.is_eof || to > from {
+                if let Err(error) = str::from_utf8(&self.buffer[from..to]) {
+                    return Ok(Some(at + error.valid_up_to()));
+                }
+                return Ok(match found {
+                    Some(found) => Some(self.offset + found),
+                    None if self.is_eof => None,
+                    None => Some(self.offset + to),
+                });
+            }
+            self.fill()?;
+        }
+    }
+
+    fn keep_from(&mut self, at: usize) {
+        self.kept = at;
+    }
+}
+
+impl<R: Read> Iterator for ReaderFindIter<'_, R> {
+    type Item = io::Result<ReaderMatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_next().transpose()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMatch {
+    line_number: u64,
+    offset: u64,
+    line: String,
+    matches: Vec<Range<usize>>,
+}
+
+impl LineMatch {
+    // Line numbers start at one.
+    pub fn line_number(&self) -> u64 {
+        self.line_number
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    // The byte ranges of the matches within the line.
+    pub fn matches(&self) -> &[Range<usize>] {
+        &self.matches
+    }
+}
+
+// Yields the lines containing a match, without their line terminator.
+#[derive(Debug)]
+pub struct LineMatches<'e, R, S = Engine> {
+    searcher: &'e S,
+    reader: R,
+    line_number: u64,
+    offset: u64,
+}
+
+impl<'e, R: BufRead, S: Searcher> LineMatches<'e, R, S> {
+    pub(crate) fn new(searcher: &'e S, reader: R) -> Self {
+        Self {
+            searcher,
+            reader,
+            line_number: 0,
+            offset: 0,
+        }
+    }
+
+    // A line that is not UTF-8 is reported as an error, and still counted so
+    // that the lines after it keep their numbers and offsets.
+    fn try_next(&mut self) -> io::Result<Option<LineMatch>> {
+        let mut bytes = Vec::new();
+        loop {
+            bytes.clear();
+            let read = self.reader.read_until(b'\n', &mut bytes)?;
+            if read == 0 {
+                return Ok(None);
+            }
+
+            let offset = self.offset;
+            self.offset += read as u64;
+            self.line_number += 1;
+
+            if bytes.ends_with(b"\n") {
+                bytes.pop();
+                if bytes.ends_with(b"\r") {
+                    bytes.pop();
+                }
+            }
+            let line = String::from_utf8(mem::take(&mut bytes))
+                .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+
+            let matches: Vec<Range<usize>> = self
+                .searcher
+                .find_iter(&line)
+                .map(|found| found.range())
+                .collect();
+            if !matches.is_empty() {
+                return Ok(Some(LineMatch {
+                    line_number: self.line_number,
+                    offset,
+                    line,
+                    matches,
+                }));
+            }
+        }
+    }
+}
+
+impl<R: BufRead, S: Searcher> Iterator for LineMatches<'_, R, S> {
+    type Item = io::Result<LineMatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_next().transpose()
+    }
+}
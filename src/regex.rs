@@ -1,4 +1,40 @@
-use crate::{epsilon_nfa::EpsilonNfa, parser::Parser};
+use std::{
+    fmt,
+    io::{BufRead, Read},
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::{
+    ast::MAX_EPSILON_NFA_STATES, glushkov::MAX_GLUSHKOV_POSITIONS, parser::Parser, search::Anchors,
+};
+
+pub use crate::{
+    accel::Accelerator,
+    aho_corasick::AhoCorasick,
+    alt,
+    ast::Ast,
+    automaton::Automaton,
+    dfa::{Dfa, DfaBuilder},
+    engine::{Engine, EngineKind},
+    epsilon_nfa::{EpsilonNfa, EpsilonNfaBuilder, Label, State},
+    equivalence::{Comparison, equivalent, equivalent_patterns, is_subset, is_subset_patterns},
+    error::{BuildError, Error, MatchKindError, SymbolBuildError},
+    glushkov::Glushkov,
+    lazy_dfa::{LazyDfa, LazyDfaCache},
+    limits::{DfaError, DfaLimits},
+    literal::{Horspool, Literal},
+    minimized_dfa::{MinimizedDfa, MinimizedDfaBuilder},
+    nfa::{Nfa, NfaBuilder},
+    pattern::{IntoSymbols, Pattern, class, escape, lit},
+    prefilter::{Prefilter, PrefilterKind},
+    reader::{LineMatch, LineMatches, ReaderFindIter, ReaderMatch},
+    search::{FindIter, Match, MatchKind, RFindIter, ReverseSearcher, Searcher},
+    seq,
+    sparse_set::Cache,
+    stream::StreamMatcher,
+    symbol::Symbol,
+};
 
 pub struct RegularExpression {
     pattern: String,
@@ -9,8 +45,358 @@ impl RegularExpression {
         Self { pattern }
     }
 
-    pub fn to_epsilon_nfa(&self) -> Result<EpsilonNfa, String> {
+    // The automata built from here match whole strings, where the `^` and `$`
+    // anchoring the pattern always hold, so they are left out. Searching with
+    // them takes a Regex.
+    pub fn to_ast(&self) -> Result<Ast, String> {
         let mut parser = Parser::new(self.pattern.as_str());
-        parser.parse()
+        parser.parse_anchored().map(|(ast, _)| ast)
+    }
+
+    pub fn to_reverse_epsilon_nfa(&self) -> Result<EpsilonNfa, String> {
+        self.to_ast()?.reverse().to_epsilon_nfa()
+    }
+
+    pub fn to_epsilon_nfa(&self) -> Result<EpsilonNfa, String> {
+        self.to_ast()?.to_epsilon_nfa()
+    }
+
+    pub fn to_glushkov(&self) -> Result<Glushkov, String> {
+        self.to_ast()?.to_glushkov()
+    }
+
+    pub fn compile(&self, limits: &DfaLimits) -> Result<Engine, Error> {
+        self.compile_with_match_kind(limits, MatchKind::default())
+    }
+
+    // Leftmost-first needs the order of the alternatives, which only the
+    // literal engines and the εNFA keep.
+    pub fn compile_with_match_kind(
+        &self,
+        limits: &DfaLimits,
+        match_kind: MatchKind,
+    ) -> Result<Engine, Error> {
+        compile_ast(
+            &self.to_ast().map_err(Error::Syntax)?,
+            &CompileOptions {
+                limits: limits.clone(),
+                match_kind,
+                size_limit: MAX_EPSILON_NFA_STATES,
+                engine: None,
+                anchored: false,
+            },
+        )
+    }
+}
+
+struct CompileOptions {
+    limits: DfaLimits,
+    match_kind: MatchKind,
+    size_limit: usize,
+    engine: Option<EngineKind>,
+    // Whether the pattern has `^` or `$`, which only the automata check.
+    anchored: bool,
+}
+
+// NFA and DFA states keep one transition per char, so classes this large are
+// left to the engines that store ranges.
+const MAX_DFA_CLASS_LEN: usize = 1 << 16;
+
+fn compile_ast(ast: &Ast, options: &CompileOptions) -> Result<Engine, Error> {
+    let CompileOptions {
+        limits,
+        match_kind,
+        size_limit,
+        engine,
+        anchored,
+    } = options;
+    let match_kind = *match_kind;
+
+    if let Some(engine) = engine {
+        // Refused before building anything, however long the construction
+        // would take.
+        if match_kind == MatchKind::LeftmostFirst && *engine != EngineKind::EpsilonNfa {
+            return Err(MatchKindError {
+                engine: *engine,
+                match_kind,
+            }
+            .into());
+        }
+        if *engine == EngineKind::Glushkov {
+            let glushkov = ast.to_glushkov().map_err(Error::Compile)?;
+            return Ok(Engine::Glushkov(glushkov.with_match_kind(match_kind)?));
+        }
+        let epsilon_nfa = ast
+            .to_epsilon_nfa_with_size_limit(*size_limit)
+            .map_err(Error::Compile)?;
+        if *engine == EngineKind::EpsilonNfa {
+            return Ok(Engine::EpsilonNfa(epsilon_nfa.with_match_kind(match_kind)));
+        }
+        let nfa = epsilon_nfa.to_nfa_with_limits(limits)?;
+        match engine {
+            EngineKind::Nfa => return Ok(Engine::Nfa(nfa.with_match_kind(match_kind)?)),
+            EngineKind::LazyDfa => {
+                return Ok(Engine::LazyDfa(
+                    nfa.to_lazy_dfa()
+                        .with_prefilter(ast.prefilter())
+                        .with_match_kind(match_kind)?,
+                ));
+            }
+            _ => {}
+        }
+        let dfa = nfa.to_dfa_with_limits(limits)?;
+        return Ok(match engine {
+            EngineKind::Dfa => Engine::Dfa(dfa.with_match_kind(match_kind)?),
+            _ => Engine::MinimizedDfa(
+                dfa.to_minimized_dfa()
+                    .with_prefilter(ast.prefilter())
+                    .with_match_kind(match_kind)?,
+            ),
+        });
+    }
+
+    if !anchored && let Some(mut literals) = ast.literals() {
+        return Ok(match literals.len() {
+            1 => Engine::Literal(Literal::new(&literals.remove(0))),
+            _ => Engine::LiteralSet(AhoCorasick::new(literals).with_match_kind(match_kind)),
+        });
+    }
+
+    if match_kind == MatchKind::LeftmostFirst {
+        return Ok(Engine::EpsilonNfa(
+            ast.to_epsilon_nfa_with_size_limit(*size_limit)
+                .map_err(Error::Compile)?
+                .with_match_kind(match_kind),
+        ));
+    }
+
+    // Patterns short enough for the bit-parallel engine need no subset
+    // construction at all.
+    if ast.position_count() <= MAX_GLUSHKOV_POSITIONS {
+        let glushkov = ast.to_glushkov().map_err(Error::Compile)?;
+        return Ok(Engine::Glushkov(glushkov.with_match_kind(match_kind)?));
+    }
+
+    // Classes too large for a DFA are left to the Thompson NFA.
+    if ast.max_class_len() > MAX_DFA_CLASS_LEN {
+        let epsilon_nfa = ast
+            .to_epsilon_nfa_with_size_limit(*size_limit)
+            .map_err(Error::Compile)?;
+        return Ok(Engine::EpsilonNfa(epsilon_nfa.with_match_kind(match_kind)));
+    }
+
+    let epsilon_nfa = ast
+        .to_epsilon_nfa_with_size_limit(*size_limit)
+        .map_err(Error::Compile)?;
+    // Only outgrowing a budget falls back to a slower engine. A cancelled or
+    // timed out compile is reported as such. Past the DFA budget the NFA is
+    // kept inside a lazy DFA rather than searched on its own: the lazy DFA
+    // builds only the DFA states a haystack reaches, and hands the search to
+    // the NFA once its cache thrashes. Past the NFA budget there is no NFA to
+    // keep, so the εNFA is searched instead.
+    let nfa = match epsilon_nfa.to_nfa_with_limits(limits) {
+        Ok(nfa) => nfa,
+        Err(err) if err.is_size_limit() => {
+            return Ok(Engine::EpsilonNfa(epsilon_nfa.with_match_kind(match_kind)));
+        }
+        Err(err) => return Err(err.into()),
+    };
+    match nfa.to_dfa_with_limits(limits) {
+        Ok(dfa) => Ok(Engine::MinimizedDfa(
+            dfa.to_minimized_dfa()
+                .with_prefilter(ast.prefilter())
+                .with_match_kind(match_kind)?,
+        )),
+        Err(err) if err.is_size_limit() => Ok(Engine::LazyDfa(
+            nfa.to_lazy_dfa()
+                .with_prefilter(ast.prefilter())
+                .with_match_kind(match_kind)?,
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+// DFAs past this size fall back to a lazy DFA, which only builds the states a
+// haystack needs.
+const DEFAULT_MAX_DFA_STATES: usize = 1 << 14;
+
+const DEFAULT_NEST_LIMIT: usize = 250;
+
+#[derive(Debug, Clone)]
+pub struct RegexBuilder {
+    pattern: String,
+    case_insensitive: bool,
+    dot_matches_new_line: bool,
+    multi_line: bool,
+    unicode: bool,
+    size_limit: usize,
+    dfa_size_limit: usize,
+    nest_limit: usize,
+    match_kind: MatchKind,
+    engine: Option<EngineKind>,
+}
+
+impl RegexBuilder {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            case_insensitive: false,
+            dot_matches_new_line: true,
+            multi_line: false,
+            unicode: false,
+            size_limit: MAX_EPSILON_NFA_STATES,
+            dfa_size_limit: DEFAULT_MAX_DFA_STATES,
+            nest_limit: DEFAULT_NEST_LIMIT,
+            match_kind: MatchKind::default(),
+            engine: None,
+        }
+    }
+
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn dot_matches_new_line(mut self, dot_matches_new_line: bool) -> Self {
+        self.dot_matches_new_line = dot_matches_new_line;
+        self
+    }
+
+    // Lets `^` and `$` match next to a newline as well as at the ends of the
+    // haystack.
+    pub fn multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    // The most εNFA states the pattern may compile to.
+    pub fn size_limit(mut self, size_limit: usize) -> Self {
+        self.size_limit = size_limit;
+        self
+    }
+
+    // The most DFA states built before falling back to a lazy DFA, or failing
+    // when the DFA was asked for.
+    pub fn dfa_size_limit(mut self, dfa_size_limit: usize) -> Self {
+        self.dfa_size_limit = dfa_size_limit;
+        self
+    }
+
+    pub fn nest_limit(mut self, nest_limit: usize) -> Self {
+        self.nest_limit = nest_limit;
+        self
+    }
+
+    pub fn match_kind(mut self, match_kind: MatchKind) -> Self {
+        self.match_kind = match_kind;
+        self
+    }
+
+    pub fn engine(mut self, engine: EngineKind) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    pub fn build(&self) -> Result<Regex, Error> {
+        let (ast, anchors) = Parser::new(&self.pattern)
+            .case_insensitive(self.case_insensitive)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .multi_line(self.multi_line)
+            .unicode(self.unicode)
+            .nest_limit(self.nest_limit)
+            .parse_anchored()
+            .map_err(Error::Syntax)?;
+        let options = CompileOptions {
+            limits: DfaLimits::new().max_states(self.dfa_size_limit),
+            match_kind: self.match_kind,
+            size_limit: self.size_limit,
+            engine: self.engine,
+            anchored: !anchors.is_empty(),
+        };
+        let engine = compile_ast(&ast, &options)?;
+        Ok(Regex {
+            pattern: self.pattern.clone(),
+            engine: Arc::new(engine),
+            anchors,
+        })
+    }
+}
+
+// A compiled pattern, running whichever engine suits it best. Clones share the
+// compiled engine.
+#[derive(Clone)]
+pub struct Regex {
+    pattern: String,
+    engine: Arc<Engine>,
+    anchors: Anchors,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        RegexBuilder::new(pattern).build()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        self.engine.is_match(input)
+    }
+
+    pub fn stream(&self) -> StreamMatcher<'_> {
+        self.engine.stream()
+    }
+
+    // `^` and `$` hold at the ends of the whole input, as they do for
+    // find_iter.
+    pub fn find_iter_in_reader<R: Read>(&self, reader: R) -> ReaderFindIter<'_, R> {
+        ReaderFindIter::new(&self.engine, reader, self.anchors)
+    }
+
+    // Matches every line on its own, so `^` and `$` hold at the line ends.
+    pub fn find_lines<R: BufRead>(&self, reader: R) -> LineMatches<'_, R, Regex> {
+        LineMatches::new(self, reader)
+    }
+}
+
+impl Searcher for Regex {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>> {
+        self.engine.find_anchored_at(haystack, start, &self.anchors)
+    }
+}
+
+impl ReverseSearcher for Regex {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>> {
+        self.engine.rfind_anchored_at(haystack, end, &self.anchors)
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Regex").field(&self.pattern).finish()
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl FromStr for Regex {
+    type Err = Error;
+
+    fn from_str(pattern: &str) -> Result<Self, Error> {
+        Regex::new(pattern)
     }
 }
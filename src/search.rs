@@ -0,0 +1,536 @@
+use std::{convert::Infallible, ops::Range};
+
+use crate::prefilter::{Prefilter, PrefilterKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'h> {
+    haystack: &'h str,
+    start: usize,
+    end: usize,
+}
+
+impl<'h> Match<'h> {
+    pub(crate) fn new(haystack: &'h str, start: usize, end: usize) -> Self {
+        Self {
+            haystack,
+            start,
+            end,
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn as_str(&self) -> &'h str {
+        &self.haystack[self.start..self.end]
+    }
+}
+
+// Which match a search reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MatchKind {
+    // The match starting furthest left that a backtracking engine would find
+    // first, preferring earlier alternatives and greedy repetitions. Only the
+    // εNFA and the literal engines keep the order of the alternatives: the
+    // DFA, NFA, MinimizedDfa and Glushkov engines refuse it with a
+    // `MatchKindError`, and compiling a pattern for it always picks the εNFA.
+    LeftmostFirst,
+    // The longest of the matches starting furthest left, as POSIX specifies.
+    #[default]
+    LeftmostLongest,
+    // The match ending first, so that the search stops as soon as any
+    // attempt matches. Of the matches ending there, the one starting furthest
+    // left is reported.
+    Earliest,
+}
+
+// The `^` and `$` anchoring every alternative of a pattern. They hold at the
+// ends of the haystack, and in multi-line mode next to a newline as well.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Anchors {
+    pub(crate) start: bool,
+    pub(crate) end: bool,
+    pub(crate) multi_line: bool,
+}
+
+impl Anchors {
+    pub(crate) fn is_empty(&self) -> bool {
+        !self.start && !self.end
+    }
+
+    pub(crate) fn is_start_allowed<I: Input + ?Sized>(&self, input: &I, at: usize) -> bool {
+        !self.start || at == 0 || (self.multi_line && input.byte_before(at) == Some(b'\n'))
+    }
+
+    pub(crate) fn is_end_allowed<I: Input + ?Sized>(
+        &self,
+        input: &mut I,
+        at: usize,
+    ) -> Result<bool, I::Error> {
+        if !self.end {
+            return Ok(true);
+        }
+        Ok(match input.byte_at(at)? {
+            Some(b) => self.multi_line && b == b'\n',
+            None => true,
+        })
+    }
+}
+
+// The text a search runs over, asked for as the search gets to it so that it
+// can be read in pieces. Offsets count bytes from the start of the text.
+pub(crate) trait Input {
+    type Error;
+
+    // The char at `at`, or None where the text ends or stops being UTF-8.
+    fn char_at(&mut self, at: usize) -> Result<Option<char>, Self::Error>;
+
+    // The byte at `at`, or None at the end of the text.
+    fn byte_at(&mut self, at: usize) -> Result<Option<u8>, Self::Error>;
+
+    // Only ever asked for the byte right before a position the search is at.
+    fn byte_before(&self, at: usize) -> Option<u8>;
+
+    // The text at hand from `at` on, for engines that scan over it in bulk.
+    fn buffered(&self, at: usize) -> &[u8];
+
+    // A char boundary no later than the first occurrence of the prefilter's
+    // literal from `at` on, or None when there is none.
+    fn find_literal(
+        &mut self,
+        prefilter: &Prefilter,
+        at: usize,
+    ) -> Result<Option<usize>, Self::Error>;
+
+    // The search no longer looks at the text before `at`.
+    fn keep_from(&mut self, _at: usize) {}
+}
+
+impl Input for &str {
+    type Error = Infallible;
+
+    fn char_at(&mut self, at: usize) -> Result<Option<char>, Infallible> {
+        Ok(self[at..].chars().next())
+    }
+
+    fn byte_at(&mut self, at: usize) -> Result<Option<u8>, Infallible> {
+        Ok(self.as_bytes().get(at).copied())
+    }
+
+    fn byte_before(&self, at: usize) -> Option<u8> {
+        at.checked_sub(1).map(|at| self.as_bytes()[at])
+    }
+
+    fn buffered(&self, at: usize) -> &[u8] {
+        &self.as_bytes()[at..]
+    }
+
+    fn find_literal(
+        &mut self,
+        prefilter: &Prefilter,
+        at: usize,
+    ) -> Result<Option<usize>, Infallible> {
+        Ok(prefilter.find_at(self, at))
+    }
+}
+
+pub(crate) fn into_ok<T>(result: Result<T, Infallible>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(never) => match never {},
+    }
+}
+
+// Every matcher reports the leftmost match, choosing among those starting at
+// the same position according to its match kind.
+pub trait Searcher {
+    fn find_at<'h>(&self, haystack: &'h str, start: usize) -> Option<Match<'h>>;
+
+    fn find<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+        self.find_at(haystack, 0)
+    }
+
+    fn find_iter<'r, 'h>(&'r self, haystack: &'h str) -> FindIter<'r, 'h, Self>
+    where
+        Self: Sized,
+    {
+        FindIter {
+            searcher: self,
+            haystack,
+            at: 0,
+            last_match_end: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FindIter<'r, 'h, S> {
+    searcher: &'r S,
+    haystack: &'h str,
+    at: usize,
+    last_match_end: Option<usize>,
+}
+
+impl<'h, S: Searcher> Iterator for FindIter<'_, 'h, S> {
+    type Item = Match<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.at > self.haystack.len() {
+                return None;
+            }
+
+            let found = self.searcher.find_at(self.haystack, self.at)?;
+            if found.is_empty() {
+                self.at = match found.end() {
+                    end if end == self.haystack.len() => end + 1,
+                    end => next_char_boundary(self.haystack, end),
+                };
+                // An empty match right where the previous match ended would
+                // report the same position twice, so it is skipped.
+                if Some(found.end()) == self.last_match_end {
+                    continue;
+                }
+            } else {
+                self.at = found.end();
+            }
+            self.last_match_end = Some(found.end());
+            return Some(found);
+        }
+    }
+}
+
+// Searches backwards from the end of the haystack, reporting the match that
+// ends furthest to the right, preferring the longest one among those ending at
+// the same position.
+pub trait ReverseSearcher {
+    fn rfind_at<'h>(&self, haystack: &'h str, end: usize) -> Option<Match<'h>>;
+
+    fn rfind<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+        self.rfind_at(haystack, haystack.len())
+    }
+
+    fn rfind_iter<'r, 'h>(&'r self, haystack: &'h str) -> RFindIter<'r, 'h, Self>
+    where
+        Self: Sized,
+    {
+        RFindIter {
+            searcher: self,
+            haystack,
+            at: Some(haystack.len()),
+            last_match_start: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RFindIter<'r, 'h, S> {
+    searcher: &'r S,
+    haystack: &'h str,
+    at: Option<usize>,
+    last_match_start: Option<usize>,
+}
+
+impl<'h, S: ReverseSearcher> Iterator for RFindIter<'_, 'h, S> {
+    type Item = Match<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let found = self.searcher.rfind_at(self.haystack, self.at?)?;
+            if found.is_empty() {
+                self.at = prev_char_boundary(self.haystack, found.start());
+                // An empty match right where the previous match started would
+                // report the same position twice, so it is skipped.
+                if Some(found.start()) == self.last_match_start {
+                    continue;
+                }
+            } else {
+                self.at = Some(found.start());
+            }
+            self.last_match_start = Some(found.start());
+            return Some(found);
+        }
+    }
+}
+
+// Searches backwards from `end` by having `find_reversed`, an engine for the
+// reversed pattern, search the reversed haystack forwards from the same place.
+// Its leftmost-longest match is the rightmost-longest one here, and the `^` and
+// `$` anchors trade places.
+pub(crate) fn rfind_reversed<'h>(
+    haystack: &'h str,
+    end: usize,
+    anchors: &Anchors,
+    find_reversed: impl for<'r> FnOnce(&'r str, usize, &Anchors) -> Option<Match<'r>>,
+) -> Option<Match<'h>> {
+    let reversed: String = haystack.chars().rev().collect();
+    let anchors = Anchors {
+        start: anchors.end,
+        end: anchors.start,
+        multi_line: anchors.multi_line,
+    };
+    let found = find_reversed(&reversed, haystack.len() - end, &anchors)?;
+    Some(Match::new(
+        haystack,
+        haystack.len() - found.end(),
+        haystack.len() - found.start(),
+    ))
+}
+
+// The start of the char ending at `at`, or None at the start of the haystack.
+pub(crate) fn prev_char_boundary(haystack: &str, at: usize) -> Option<usize> {
+    haystack[..at].char_indices().next_back().map(|(i, _)| i)
+}
+
+// The byte offset just past the char starting at `at`, used to step over a
+// position without splitting a multi-byte char.
+pub(crate) fn next_char_boundary(haystack: &str, at: usize) -> usize {
+    at + haystack[at..].chars().next().map_or(1, char::len_utf8)
+}
+
+// An engine `find_leftmost` can run from every start position at once. Each
+// attempt is a thread holding the states it is in, and the threads are kept in
+// the order they started.
+pub(crate) trait Threads {
+    type State: Copy + Eq;
+    // The states the threads stepped so far hold.
+    type Held: Default;
+
+    fn start_state(&self) -> Self::State;
+
+    fn next_states(&self, state: Self::State, c: char, push: impl FnMut(Self::State));
+
+    // Takes out of `state` whatever `held` already holds and holds the rest,
+    // returning it unless nothing is left.
+    fn hold(&self, held: &mut Self::Held, state: Self::State) -> Option<Self::State>;
+
+    fn release(&self, held: &mut Self::Held);
+
+    fn thread_accepts(&self, state: Self::State) -> bool;
+
+    // How many of the bytes at hand the thread can go over without leaving
+    // `state`.
+    fn skip(&self, _state: Self::State, _buffered: &[u8]) -> usize {
+        0
+    }
+
+    // Whether the engine would rather another one finished the search, asked
+    // after every release.
+    fn gives_up(&self) -> bool {
+        false
+    }
+}
+
+// How a search ended that its engine may give up on.
+pub(crate) enum Leftmost {
+    Done(Option<(usize, usize)>),
+    // No match can start before the position, which is still in the input.
+    GaveUp(usize),
+}
+
+// The positions the prefilter allows matches to start at, searched for as the
+// search moves on.
+struct Starts<'p> {
+    prefilter: Option<&'p Prefilter>,
+    anchors: &'p Anchors,
+    found: Option<Option<usize>>,
+}
+
+impl<'p> Starts<'p> {
+    fn new(prefilter: Option<&'p Prefilter>, anchors: &'p Anchors) -> Self {
+        Self {
+            prefilter,
+            anchors,
+            found: None,
+        }
+    }
+
+    // The first start from `at` on that the prefilter allows, or None once no
+    // match can start any more. The `^` anchor is left to the caller, except
+    // that without multi-line mode nothing starts past the start of the text.
+    fn next<I: Input + ?Sized>(
+        &mut self,
+        input: &mut I,
+        at: usize,
+    ) -> Result<Option<usize>, I::Error> {
+        if self.anchors.start && !self.anchors.multi_line && at > 0 {
+            return Ok(None);
+        }
+        let Some(prefilter) = self.prefilter else {
+            return Ok(Some(at));
+        };
+        if self
+            .found
+            .is_none_or(|found| found.is_some_and(|found| found < at))
+        {
+            self.found = Some(input.find_literal(prefilter, at)?);
+        }
+
+        let Some(found) = self.found.flatten() else {
+            return Ok(None);
+        };
+        Ok(Some(match prefilter.kind() {
+            PrefilterKind::Prefix => found,
+            // The literal only has to occur somewhere in the match.
+            PrefilterKind::Inner => at,
+        }))
+    }
+}
+
+pub(crate) fn find_leftmost<'h, T: Threads>(
+    engine: &T,
+    haystack: &'h str,
+    start: usize,
+    prefilter: Option<&Prefilter>,
+    match_kind: MatchKind,
+    anchors: &Anchors,
+) -> Option<Match<'h>> {
+    let found = into_ok(find_leftmost_in(
+        engine,
+        &mut { haystack },
+        start,
+        prefilter,
+        match_kind,
+        anchors,
+    ));
+    found.map(|(start, end)| Match::new(haystack, start, end))
+}
+
+// Runs an attempt from every start position the prefilter and the anchors
+// allow in a single pass over the input, only accepting where `$` holds.
+// Threads holding the same state see the same future, so only the one that
+// started first keeps it and each position costs at most one step per state. An
+// earliest search stops at the first match. Otherwise no more threads start
+// once a match is found, and the search goes on while a thread that started no
+// later than the match is left, since it may still find a match further left or
+// a longer one. Nothing before the first thread is looked at again.
+pub(crate) fn find_leftmost_in<T: Threads, I: Input + ?Sized>(
+    engine: &T,
+    input: &mut I,
+    start: usize,
+    prefilter: Option<&Prefilter>,
+    match_kind: MatchKind,
+    anchors: &Anchors,
+) -> Result<Option<(usize, usize)>, I::Error> {
+    match try_find_leftmost_in(engine, input, start, prefilter, match_kind, anchors)? {
+        Leftmost::Done(found) => Ok(found),
+        Leftmost::GaveUp(_) => unreachable!("only the lazy DFA gives up on a search"),
+    }
+}
+
+// Stops where the engine gives up, at the start of the first thread, so that
+// searching again from there finds the same match.
+pub(crate) fn try_find_leftmost_in<T: Threads, I: Input + ?Sized>(
+    engine: &T,
+    input: &mut I,
+    start: usize,
+    prefilter: Option<&Prefilter>,
+    match_kind: MatchKind,
+    anchors: &Anchors,
+) -> Result<Leftmost, I::Error> {
+    let earliest = match_kind == MatchKind::Earliest;
+    let mut starts = Starts::new(prefilter, anchors);
+    let mut threads: Vec<(T::State, usize)> = Vec::new();
+    let mut next_threads: Vec<(T::State, usize)> = Vec::new();
+    let mut held = T::Held::default();
+    let mut found: Option<(usize, usize)> = None;
+    let mut at = start;
+    loop {
+        if found.is_none() {
+            let next_start = starts.next(input, at)?;
+            if threads.is_empty() {
+                match next_start {
+                    Some(next_start) => at = next_start,
+                    None => return Ok(Leftmost::Done(None)),
+                }
+            }
+            if next_start == Some(at)
+                && anchors.is_start_allowed(input, at)
+                && let Some(state) = engine.hold(&mut held, engine.start_state())
+            {
+                threads.push((state, at));
+            }
+        }
+        input.keep_from(
+            threads
+                .first()
+                .map_or(at, |&(_, thread_start)| thread_start),
+        );
+
+        if anchors.is_end_allowed(input, at)?
+            && let Some(&(_, thread_start)) = threads
+                .iter()
+                .find(|&&(state, _)| engine.thread_accepts(state))
+        {
+            if earliest {
+                return Ok(Leftmost::Done(Some((thread_start, at))));
+            }
+            if found.is_none_or(|(match_start, _)| thread_start <= match_start) {
+                found = Some((thread_start, at));
+            }
+            let match_start = found.map_or(thread_start, |(match_start, _)| match_start);
+            threads.retain(|&(_, thread_start)| thread_start <= match_start);
+        }
+
+        let c = input.char_at(at)?;
+        if threads.is_empty() {
+            match c {
+                Some(c) if found.is_none() => at += c.len_utf8(),
+                _ => break,
+            }
+            continue;
+        }
+        let Some(c) = c else {
+            break;
+        };
+
+        // A lone thread can skip ahead when no other thread can start on the
+        // way, either because there is a match already or because a thread
+        // starting there would be in the same state. It must not skip over
+        // the line ends where `$` may hold, though.
+        if let [(state, _)] = threads[..]
+            && (found.is_some() || state == engine.start_state())
+            && !(anchors.end && anchors.multi_line)
+        {
+            let skipped = engine.skip(state, input.buffered(at));
+            if skipped > 0 {
+                at += skipped;
+                continue;
+            }
+        }
+
+        engine.release(&mut held);
+        if engine.gives_up() {
+            return Ok(Leftmost::GaveUp(threads[0].1));
+        }
+        for &(state, thread_start) in &threads {
+            engine.next_states(state, c, |next| {
+                if let Some(next) = engine.hold(&mut held, next) {
+                    next_threads.push((next, thread_start));
+                }
+            });
+        }
+        std::mem::swap(&mut threads, &mut next_threads);
+        next_threads.clear();
+        at += c.len_utf8();
+    }
+
+    Ok(Leftmost::Done(found))
+}
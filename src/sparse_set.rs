@@ -0,0 +1,97 @@
+use std::{collections::VecDeque, mem};
+
+use crate::StateId;
+
+#[derive(Debug, Clone)]
+pub struct SparseSet {
+    dense: Vec<StateId>,
+    sparse: Vec<usize>,
+}
+
+impl SparseSet {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            dense: Vec::with_capacity(capacity),
+            sparse: vec![0; capacity],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.sparse.len()
+    }
+
+    pub fn resize(&mut self, capacity: usize) {
+        self.clear();
+        self.sparse.resize(capacity, 0);
+        self.dense.reserve(capacity);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    pub fn contains(&self, state: StateId) -> bool {
+        let index = self.sparse[state];
+        index < self.dense.len() && self.dense[index] == state
+    }
+
+    pub fn insert(&mut self, state: StateId) -> bool {
+        if self.contains(state) {
+            return false;
+        }
+
+        self.sparse[state] = self.dense.len();
+        self.dense.push(state);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.dense.clear();
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, StateId> {
+        self.dense.iter()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    pub(crate) current: SparseSet,
+    pub(crate) next: SparseSet,
+    pub(crate) current_counts: Vec<VecDeque<usize>>,
+    pub(crate) next_counts: Vec<VecDeque<usize>>,
+}
+
+impl Cache {
+    pub fn new(states: usize) -> Self {
+        Self {
+            current: SparseSet::new(states),
+            next: SparseSet::new(states),
+            current_counts: vec![VecDeque::new(); states],
+            next_counts: vec![VecDeque::new(); states],
+        }
+    }
+
+    pub(crate) fn reset(&mut self, states: usize) {
+        if self.current.capacity() < states {
+            self.current.resize(states);
+            self.next.resize(states);
+            self.current_counts.resize(states, VecDeque::new());
+            self.next_counts.resize(states, VecDeque::new());
+        }
+        self.current.clear();
+        self.next.clear();
+        for counts in self.current_counts.iter_mut().chain(&mut self.next_counts) {
+            counts.clear();
+        }
+    }
+
+    pub(crate) fn swap(&mut self) {
+        for &state in self.current.iter() {
+            self.current_counts[state].clear();
+        }
+        mem::swap(&mut self.current, &mut self.next);
+        mem::swap(&mut self.current_counts, &mut self.next_counts);
+        self.next.clear();
+    }
+}
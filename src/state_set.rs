@@ -0,0 +1,51 @@
+use crate::StateId;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateSet {
+    words: Vec<u64>,
+}
+
+impl StateSet {
+    pub fn new(states: usize) -> Self {
+        Self {
+            words: vec![0; states.div_ceil(WORD_BITS)],
+        }
+    }
+
+    pub fn insert(&mut self, state: StateId) {
+        self.words[state / WORD_BITS] |= 1 << (state % WORD_BITS);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    pub fn union_with(&mut self, other: &StateSet) {
+        for (word, &other_word) in self.words.iter_mut().zip(&other.words) {
+            *word |= other_word;
+        }
+    }
+
+    pub fn intersects(&self, other: &StateSet) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .any(|(&word, &other_word)| word & other_word != 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = StateId> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(index * WORD_BITS + bit)
+            })
+        })
+    }
+}
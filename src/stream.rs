@@ -0,0 +1,261 @@
+use std::str;
+
+use crate::{
+    StateId,
+    aho_corasick::{AhoCorasick, ROOT},
+    dfa::Dfa,
+    epsilon_nfa::EpsilonNfa,
+    glushkov::{Glushkov, INITIAL},
+    literal::Literal,
+    minimized_dfa::MinimizedDfa,
+    nfa::Nfa,
+    sparse_set::Cache,
+};
+
+#[derive(Debug, Clone)]
+enum Cursor<'a> {
+    Literal {
+        literal: &'a Literal,
+        matched: Option<usize>,
+    },
+    LiteralSet {
+        aho_corasick: &'a AhoCorasick,
+        state: Option<StateId>,
+    },
+    Glushkov {
+        glushkov: &'a Glushkov,
+        active: u64,
+    },
+    EpsilonNfa {
+        epsilon_nfa: &'a EpsilonNfa,
+        cache: Cache,
+        position: usize,
+    },
+    Nfa {
+        nfa: &'a Nfa,
+        cache: Cache,
+    },
+    Dfa {
+        dfa: &'a Dfa,
+        state: Option<StateId>,
+    },
+    MinimizedDfa {
+        minimized_dfa: &'a MinimizedDfa,
+        state: Option<StateId>,
+    },
+}
+
+impl Cursor<'_> {
+    fn step(&mut self, c: char) {
+        match self {
+            Cursor::Literal { literal, matched } => {
+                *matched = matched.filter(|&matched| literal.as_str()[matched..].starts_with(c));
+                if let Some(matched) = matched {
+                    *matched += c.len_utf8();
+                }
+            }
+            Cursor::LiteralSet {
+                aho_corasick,
+                state,
+            } => *state = state.and_then(|state| aho_corasick.next_state(state, c)),
+            Cursor::Glushkov { glushkov, active } => *active = glushkov.step(*active, c),
+            Cursor::EpsilonNfa {
+                epsilon_nfa,
+                cache,
+                position,
+            } => {
+                epsilon_nfa.step_cache(cache, *position, c);
+                *position += 1;
+            }
+            Cursor::Nfa { nfa, cache } => nfa.step_cache(cache, c),
+            Cursor::Dfa { dfa, state } => *state = state.and_then(|state| dfa.next_state(state, c)),
+            Cursor::MinimizedDfa {
+                minimized_dfa,
+                state,
+            } => *state = state.and_then(|state| minimized_dfa.next_state(state, c)),
+        }
+    }
+
+    fn is_accepting(&self) -> bool {
+        match self {
+            Cursor::Literal { literal, matched } => *matched == Some(literal.as_str().len()),
+            Cursor::LiteralSet {
+                aho_corasick,
+                state,
+            } => state.is_some_and(|state| aho_corasick.is_pattern(state)),
+            Cursor::Glushkov { glushkov, active } => glushkov.is_accepting(*active),
+            Cursor::EpsilonNfa {
+                epsilon_nfa, cache, ..
+            } => epsilon_nfa.is_cache_accepting(cache),
+            Cursor::Nfa { nfa, cache } => nfa.is_cache_accepting(cache),
+            Cursor::Dfa { dfa, state } => state.is_some_and(|state| dfa.is_accepting_state(state)),
+            Cursor::MinimizedDfa {
+                minimized_dfa,
+                state,
+            } => state.is_some_and(|state| minimized_dfa.is_accepting_state(state)),
+        }
+    }
+
+    fn is_dead(&self) -> bool {
+        match self {
+            Cursor::Literal { matched, .. } => matched.is_none(),
+            Cursor::LiteralSet { state, .. }
+            | Cursor::Dfa { state, .. }
+            | Cursor::MinimizedDfa { state, .. } => state.is_none(),
+            Cursor::Glushkov { active, .. } => *active == 0,
+            Cursor::EpsilonNfa { cache, .. } | Cursor::Nfa { cache, .. } => {
+                cache.current.is_empty()
+            }
+        }
+    }
+}
+
+// Matches input pushed in chunks as if it had been passed to is_match in one
+// piece. A clone is a snapshot of the match so far that can be resumed
+// independently.
+#[derive(Debug, Clone)]
+pub struct StreamMatcher<'a> {
+    cursor: Cursor<'a>,
+    // The leading bytes of a UTF-8 sequence cut off by the end of a chunk.
+    pending: Vec<u8>,
+    is_invalid: bool,
+}
+
+impl<'a> StreamMatcher<'a> {
+    fn new(cursor: Cursor<'a>) -> Self {
+        Self {
+            cursor,
+            pending: Vec::new(),
+            is_invalid: false,
+        }
+    }
+
+    pub(crate) fn for_literal(literal: &'a Literal) -> Self {
+        Self::new(Cursor::Literal {
+            literal,
+            matched: Some(0),
+        })
+    }
+
+    pub(crate) fn for_literal_set(aho_corasick: &'a AhoCorasick) -> Self {
+        Self::new(Cursor::LiteralSet {
+            aho_corasick,
+            state: Some(ROOT),
+        })
+    }
+
+    pub(crate) fn for_glushkov(glushkov: &'a Glushkov) -> Self {
+        Self::new(Cursor::Glushkov {
+            glushkov,
+            active: INITIAL,
+        })
+    }
+
+    pub(crate) fn for_epsilon_nfa(epsilon_nfa: &'a EpsilonNfa) -> Self {
+        let mut cache = epsilon_nfa.create_cache();
+        epsilon_nfa.start_cache(&mut cache);
+        Self::new(Cursor::EpsilonNfa {
+            epsilon_nfa,
+            cache,
+            position: 0,
+        })
+    }
+
+    pub(crate) fn for_nfa(nfa: &'a Nfa) -> Self {
+        let mut cache = nfa.create_cache();
+        nfa.start_cache(&mut cache, [nfa.start()]);
+        Self::new(Cursor::Nfa { nfa, cache })
+    }
+
+    pub(crate) fn for_dfa(dfa: &'a Dfa) -> Self {
+        Self::new(Cursor::Dfa {
+            dfa,
+            state: Some(dfa.start()),
+        })
+    }
+
+    pub(crate) fn for_minimized_dfa(minimized_dfa: &'a MinimizedDfa) -> Self {
+        Self::new(Cursor::MinimizedDfa {
+            minimized_dfa,
+            state: Some(minimized_dfa.start()),
+        })
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if self.is_dead() {
+            return;
+        }
+
+        let mut rest = chunk;
+        if let Some(&lead) = self.pending.first() {
+            let missing = utf8_width(lead) - self.pending.len();
+            let taken = missing.min(rest.len());
+            self.pending.extend_from_slice(&rest[..taken]);
+            rest = &rest[taken..];
+            if taken < missing {
+                return;
+            }
+
+            match str::from_utf8(&self.pending) {
+                Ok(completed) => {
+                    for c in completed.chars() {
+                        self.cursor.step(c);
+                    }
+                }
+                Err(_) => {
+                    self.is_invalid = true;
+                    return;
+                }
+            }
+            self.pending.clear();
+        }
+
+        let valid = match str::from_utf8(rest) {
+            Ok(valid) => valid,
+            Err(error) => {
+                if error.error_len().is_some() {
+                    self.is_invalid = true;
+                    return;
+                }
+                self.pending.extend_from_slice(&rest[error.valid_up_to()..]);
+                str::from_utf8(&rest[..error.valid_up_to()])
+                    .expect("the prefix up to valid_up_to() is valid UTF-8")
+            }
+        };
+        for c in valid.chars() {
+            self.cursor.step(c);
+            if self.cursor.is_dead() {
+                return;
+            }
+        }
+    }
+
+    pub fn feed_str(&mut self, chunk: &str) {
+        self.feed(chunk.as_bytes());
+    }
+
+    // Whether the input fed so far, taken as a whole, is matched.
+    pub fn is_accepting(&self) -> bool {
+        !self.is_invalid && self.pending.is_empty() && self.cursor.is_accepting()
+    }
+
+    // Whether no continuation of the input fed so far can be matched.
+    pub fn is_dead(&self) -> bool {
+        self.is_invalid || self.cursor.is_dead()
+    }
+
+    pub fn finish(self) -> bool {
+        self.is_accepting()
+    }
+}
+
+// The length of the UTF-8 sequence a byte starts, treating bytes that cannot
+// start one as a sequence of their own so that decoding them fails right away.
+pub(crate) fn utf8_width(lead: u8) -> usize {
+    match lead {
+        0xF0..=0xF7 => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
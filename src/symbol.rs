@@ -0,0 +1,37 @@
+use std::{fmt::Debug, hash::Hash, ops::RangeInclusive};
+
+// What an automaton reads: chars for patterns, but bytes, lexer tokens or
+// event types work just as well.
+pub trait Symbol: Copy + Ord + Hash + Debug {
+    // The symbol right after this one, or None for the last one, which is how
+    // a class given as a range is walked symbol by symbol.
+    fn successor(self) -> Option<Self>;
+}
+
+impl Symbol for char {
+    fn successor(self) -> Option<char> {
+        match self {
+            '\u{D7FF}' => Some('\u{E000}'),
+            c => char::from_u32(c as u32 + 1),
+        }
+    }
+}
+
+impl Symbol for u8 {
+    fn successor(self) -> Option<u8> {
+        self.checked_add(1)
+    }
+}
+
+// The symbols of a range, in order.
+pub(crate) fn range_symbols<A: Symbol>(
+    range: &RangeInclusive<A>,
+) -> impl Iterator<Item = A> + use<A> {
+    let end = *range.end();
+    let mut next = (range.start() <= range.end()).then_some(*range.start());
+    std::iter::from_fn(move || {
+        let curr = next?;
+        next = curr.successor().filter(|&successor| successor <= end);
+        Some(curr)
+    })
+}
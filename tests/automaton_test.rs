@@ -0,0 +1,218 @@
+use std::collections::{HashSet, VecDeque};
+
+use dumb_regex::regex::{Automaton, NfaBuilder, RegularExpression};
+
+const PATTERNS: [&str; 5] = ["(a|b)*abb", "a[0-9]?|b{2,3}", "(ab)+|c*", "x{2,}y", "a|b"];
+
+const INPUTS: [&str; 12] = [
+    "", "abb", "babb", "ab", "a", "a7", "bb", "bbbb", "ababab", "ccc", "xxy", "xy",
+];
+
+// Whole strings each pattern does and does not match, which every engine must
+// agree on.
+const MATCHES: &[(&str, &[(&str, bool)])] = &[
+    ("a", &[("a", true), ("b", false), ("ab", false)]),
+    ("a*", &[("aaaaaaaaaaa", true), ("aaaaaaaaaabaaaaaa", false)]),
+    ("a|b|c", &[("a", true), ("b", true), ("d", false)]),
+    ("[hc]at", &[("hat", true), ("cat", true), ("mat", false)]),
+    (
+        ".at",
+        &[("hat", true), ("cat", true), ("mat", true), ("pat", true)],
+    ),
+    (
+        "([hc]at)?[mp]at",
+        &[
+            ("mat", true),
+            ("hat", false),
+            ("pat", true),
+            ("catmat", true),
+        ],
+    ),
+    ("[a-zA-Z0-9]", &[("5", true), ("G", true), ("@", false)]),
+    ("[^a-zA-Z0-9]", &[("5", false), ("G", false), ("@", true)]),
+    (
+        "\\w*",
+        &[
+            ("0123", true),
+            ("ZYX", true),
+            ("abcd", true),
+            ("abcdef_ABCDEF___01234", true),
+            ("0+1-2", false),
+        ],
+    ),
+    (
+        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
+        &[
+            ("1", true),
+            ("1000000", true),
+            ("-1", true),
+            ("1e9", true),
+            ("1e-5", true),
+            ("1E-5", true),
+            ("1e-12233342", true),
+            ("3.1415926535", true),
+            ("237429342e24801", true),
+            ("6.022e+23", true),
+            ("e+23", false),
+            ("abcd", false),
+            ("abcd123", false),
+            ("123abcd", false),
+        ],
+    ),
+    ("(a|b)*abb(a|b)*", &[("aaaabbbbbb", true)]),
+    ("(a*|b*)*", &[("", true)]),
+    ("(a|b){0}", &[("", true)]),
+    ("(a|b){0,0}", &[("", true), ("a", false)]),
+    ("(a|b){0,1}", &[("", true), ("a", true), ("ab", false)]),
+    (
+        "(a|b){2,4}",
+        &[
+            ("", false),
+            ("a", false),
+            ("ba", true),
+            ("aba", true),
+            ("aaba", true),
+            ("abbaa", false),
+        ],
+    ),
+    ("(a|b){2,}", &[("aaaaaaaaaaaa", true)]),
+    ("(a|b){2}", &[("a", false), ("abb", false)]),
+    ("(a|b){10,10}", &[("abaaa", false)]),
+    (
+        "[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\\.[a-zA-Z]{2,}",
+        &[("john.smith@example.com", true)],
+    ),
+];
+
+// Steps through the input one symbol at a time.
+fn run<A: Automaton<Symbol = char>>(automaton: &A, input: &str) -> bool {
+    let mut states = automaton.start_states();
+    for c in input.chars() {
+        automaton.step(&mut states, c);
+    }
+    automaton.is_accepting(&states)
+}
+
+// The length of the shortest accepted string over the given symbols.
+fn shortest_len<A: Automaton<Symbol = char>>(automaton: &A, alphabet: &[char]) -> Option<usize> {
+    let start = automaton.start_states();
+    let mut seen: HashSet<A::States> = HashSet::from([start.clone()]);
+    let mut queue: VecDeque<(A::States, usize)> = VecDeque::from([(start, 0)]);
+    while let Some((states, len)) = queue.pop_front() {
+        if automaton.is_accepting(&states) {
+            return Some(len);
+        }
+        for &c in alphabet {
+            let mut next = states.clone();
+            automaton.step(&mut next, c);
+            if seen.insert(next.clone()) {
+                queue.push_back((next, len + 1));
+            }
+        }
+    }
+    None
+}
+
+fn edge_count<A: Automaton>(automaton: &A) -> usize {
+    automaton.transitions().count()
+}
+
+#[test]
+fn automaton_test_1() {
+    for pattern in PATTERNS {
+        let epsilon_nfa = RegularExpression::new(pattern.to_string())
+            .to_epsilon_nfa()
+            .unwrap();
+        let nfa = epsilon_nfa.to_nfa();
+        let dfa = nfa.to_dfa();
+        let minimized_dfa = dfa.to_minimized_dfa();
+        for input in INPUTS {
+            let expected = epsilon_nfa.is_match(input);
+            assert_eq!(run(&epsilon_nfa, input), expected, "{pattern} {input}");
+            assert_eq!(run(&nfa, input), expected, "{pattern} {input}");
+            assert_eq!(run(&dfa, input), expected, "{pattern} {input}");
+            assert_eq!(run(&minimized_dfa, input), expected, "{pattern} {input}");
+        }
+    }
+}
+
+#[test]
+fn automaton_test_2() {
+    let alphabet = ['a', 'b', 'c', 'x', 'y', '7'];
+    for (pattern, expected) in [
+        ("(a|b)*abb", Some(3)),
+        ("a[0-9]?|b{2,3}", Some(1)),
+        ("b{2,3}a", Some(3)),
+        ("x{2,}y", Some(3)),
+        ("(ab)+|c*", Some(0)),
+        ("z", None),
+    ] {
+        let epsilon_nfa = RegularExpression::new(pattern.to_string())
+            .to_epsilon_nfa()
+            .unwrap();
+        let nfa = epsilon_nfa.to_nfa();
+        let dfa = nfa.to_dfa();
+        let minimized_dfa = dfa.to_minimized_dfa();
+        assert_eq!(shortest_len(&epsilon_nfa, &alphabet), expected, "{pattern}");
+        assert_eq!(shortest_len(&nfa, &alphabet), expected, "{pattern}");
+        assert_eq!(shortest_len(&dfa, &alphabet), expected, "{pattern}");
+        assert_eq!(
+            shortest_len(&minimized_dfa, &alphabet),
+            expected,
+            "{pattern}"
+        );
+    }
+}
+
+#[test]
+fn automaton_test_3() {
+    let epsilon_nfa = RegularExpression::new("x{2,}y".to_string())
+        .to_epsilon_nfa()
+        .unwrap();
+    let nfa = epsilon_nfa.to_nfa();
+    assert!(!Automaton::is_empty(&epsilon_nfa));
+    assert!(!Automaton::is_empty(&nfa));
+    assert!(!Automaton::is_empty(&nfa.to_dfa().to_minimized_dfa()));
+
+    // The accepting state cannot be reached from the start.
+    let mut builder = NfaBuilder::new();
+    let start = builder.add_state();
+    let end = builder.add_state();
+    builder.set_start(start).unwrap();
+    builder.add_transition(end, 'a', start).unwrap();
+    builder.set_accepting(end).unwrap();
+    let nfa = builder.build().unwrap();
+    assert!(Automaton::is_empty(&nfa));
+    assert!(Automaton::is_empty(&nfa.to_dfa()));
+    assert_eq!(edge_count(&nfa.to_dfa().to_minimized_dfa()), 0);
+}
+
+#[test]
+fn automaton_test_4() {
+    for &(pattern, cases) in MATCHES {
+        let re = RegularExpression::new(pattern.to_string());
+        let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+        let nfa = epsilon_nfa.to_nfa();
+        let dfa = nfa.to_dfa();
+        let minimized_dfa = dfa.to_minimized_dfa();
+        let lazy_dfa = nfa.to_lazy_dfa();
+        let glushkov = re.to_glushkov().unwrap();
+        for &(input, expected) in cases {
+            let engines = [
+                ("epsilon_nfa", epsilon_nfa.is_match(input)),
+                ("epsilon_nfa steps", run(&epsilon_nfa, input)),
+                ("nfa", nfa.is_match(input)),
+                ("nfa steps", run(&nfa, input)),
+                ("dfa", dfa.is_match(input)),
+                ("dfa steps", run(&dfa, input)),
+                ("minimized_dfa", minimized_dfa.is_match(input)),
+                ("minimized_dfa steps", run(&minimized_dfa, input)),
+                ("lazy_dfa", lazy_dfa.is_match(input)),
+                ("glushkov", glushkov.is_match(input)),
+            ];
+            for (engine, is_match) in engines {
+                assert_eq!(is_match, expected, "{engine} {pattern:?} {input:?}");
+            }
+        }
+    }
+}
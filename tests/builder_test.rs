@@ -0,0 +1,144 @@
+use dumb_regex::regex::{
+    BuildError, DfaBuilder, EpsilonNfaBuilder, MatchKind, MinimizedDfaBuilder, NfaBuilder, Searcher,
+};
+
+#[test]
+fn builder_test_1() {
+    // (a|b)*abb, built the way the textbook draws it.
+    let mut builder = EpsilonNfaBuilder::new();
+    let start = builder.add_state();
+    let loop_state = builder.add_state();
+    let after_a = builder.add_state();
+    let after_ab = builder.add_state();
+    let end = builder.add_state();
+    builder.set_start(start).unwrap();
+    builder.add_epsilon_transition(start, loop_state).unwrap();
+    builder
+        .add_range_transition(loop_state, 'a'..='b', loop_state)
+        .unwrap();
+    builder.add_transition(loop_state, 'a', after_a).unwrap();
+    builder.add_transition(after_a, 'b', after_ab).unwrap();
+    builder.add_transition(after_ab, 'b', end).unwrap();
+    builder.set_accepting(end).unwrap();
+    let epsilon_nfa = builder.build().unwrap();
+
+    let nfa = epsilon_nfa.to_nfa();
+    let dfa = nfa.to_dfa();
+    let minimized_dfa = dfa.to_minimized_dfa();
+    for input in ["abb", "aabb", "babb", "ab", "abba", ""] {
+        let expected = input.ends_with("abb");
+        assert_eq!(epsilon_nfa.is_match(input), expected, "{input}");
+        assert_eq!(nfa.is_match(input), expected, "{input}");
+        assert_eq!(dfa.is_match(input), expected, "{input}");
+        assert_eq!(minimized_dfa.is_match(input), expected, "{input}");
+    }
+    // The four states of the textbook automaton, plus the dead state.
+    assert_eq!(minimized_dfa.state_count(), 5);
+}
+
+#[test]
+fn builder_test_2() {
+    let mut builder = EpsilonNfaBuilder::new();
+    let state = builder.add_state();
+    assert_eq!(
+        builder.add_transition(state, 'a', 7),
+        Err(BuildError::UnknownState(7))
+    );
+    assert_eq!(
+        builder.add_epsilon_transition(3, state),
+        Err(BuildError::UnknownState(3))
+    );
+    assert_eq!(builder.set_accepting(9), Err(BuildError::UnknownState(9)));
+    assert!(matches!(builder.build(), Err(BuildError::MissingStart)));
+
+    let mut builder = NfaBuilder::new();
+    let state = builder.add_state();
+    assert_eq!(builder.set_start(1), Err(BuildError::UnknownState(1)));
+    builder.set_start(state).unwrap();
+    assert!(builder.build().is_ok());
+}
+
+#[test]
+fn builder_test_3() {
+    // Strings over {a, b} whose second to last symbol is an `a`.
+    let mut builder = NfaBuilder::new();
+    let start = builder.add_state();
+    let after_a = builder.add_state();
+    let end = builder.add_state();
+    builder.set_start(start).unwrap();
+    for c in ['a', 'b'] {
+        builder.add_transition(start, c, start).unwrap();
+        builder.add_transition(after_a, c, end).unwrap();
+    }
+    builder.add_transition(start, 'a', after_a).unwrap();
+    builder.set_accepting(end).unwrap();
+    let nfa = builder.build().unwrap();
+
+    let minimized_dfa = nfa.to_dfa().to_minimized_dfa();
+    for (input, expected) in [("ab", true), ("bbaa", true), ("ba", false), ("a", false)] {
+        assert_eq!(nfa.is_match(input), expected, "{input}");
+        assert_eq!(minimized_dfa.is_match(input), expected, "{input}");
+    }
+    let found: Vec<&str> = nfa.find_iter("bbab").map(|m| m.as_str()).collect();
+    assert_eq!(found, vec!["bbab"]);
+}
+
+#[test]
+fn builder_test_4() {
+    let mut builder = DfaBuilder::new();
+    let start = builder.add_state();
+    let end = builder.add_state();
+    builder.set_start(start).unwrap();
+    builder.add_transition(start, 'x', end).unwrap();
+    // Repeating an edge is fine, giving it a second target is not.
+    builder.add_transition(start, 'x', end).unwrap();
+    assert_eq!(
+        builder.add_transition(start, 'x', start),
+        Err(BuildError::Nondeterministic {
+            state: start,
+            symbol: 'x'
+        })
+    );
+    // The dead state is not the caller's to wire up.
+    assert_eq!(
+        builder.add_transition(0, 'x', end),
+        Err(BuildError::UnknownState(0))
+    );
+    builder.add_transition(end, 'x', end).unwrap();
+    builder.set_accepting(end).unwrap();
+    let dfa = builder.build().unwrap();
+
+    assert!(dfa.is_match("xxx"));
+    assert!(!dfa.is_match(""));
+    assert_eq!(dfa.to_minimized_dfa().state_count(), 3);
+    let reversed = dfa.reverse();
+    assert!(reversed.is_match("xx"));
+}
+
+#[test]
+fn builder_test_5() {
+    // Two equivalent accepting states are merged on build.
+    let mut builder = MinimizedDfaBuilder::new();
+    let start = builder.add_state();
+    let left = builder.add_state();
+    let right = builder.add_state();
+    builder.set_start(start).unwrap();
+    builder.add_transition(start, 'l', left).unwrap();
+    builder.add_transition(start, 'r', right).unwrap();
+    builder.set_accepting(left).unwrap();
+    builder.set_accepting(right).unwrap();
+    let minimized_dfa = builder
+        .build()
+        .unwrap()
+        .with_match_kind(MatchKind::Earliest)
+        .unwrap();
+
+    assert_eq!(minimized_dfa.state_count(), 3);
+    assert_eq!(minimized_dfa.accepting_states().count(), 1);
+    let found: Vec<&str> = minimized_dfa
+        .find_iter("xlrx")
+        .map(|m| m.as_str())
+        .collect();
+    assert_eq!(found, vec!["l", "r"]);
+    assert!(format!("{}", BuildError::MissingStart).contains("start"));
+}
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+use dumb_regex::regex::{DfaError, DfaLimits, Engine, Error, RegularExpression};
+
+#[test]
+fn compile_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*abb"));
+    let engine = re.compile(&DfaLimits::new()).unwrap();
+
+    assert!(matches!(engine, Engine::Glushkov(_)));
+    assert!(engine.is_match("ababb"));
+}
+
+#[test]
+fn compile_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){40}"));
+    let engine = re.compile(&DfaLimits::new().max_states(1000)).unwrap();
+
+    assert!(matches!(engine, Engine::LazyDfa(_)));
+    assert!(engine.is_match(&format!("bbb{}", "a".repeat(41))));
+    assert!(!engine.is_match(&format!("bbba{}", "b".repeat(41))));
+}
+
+#[test]
+fn compile_test_3() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a"));
+
+    assert!(re.compile(&DfaLimits::new()).is_err());
+}
+
+#[test]
+fn compile_test_4() {
+    let re: RegularExpression = RegularExpression::new(String::from("x\\d{1,100000}y"));
+    let engine = re.compile(&DfaLimits::new().max_nfa_states(10000)).unwrap();
+
+    assert!(matches!(engine, Engine::EpsilonNfa(_)));
+    assert!(engine.is_match(&format!("x{}y", "7".repeat(50000))));
+    assert!(!engine.is_match("xy"));
+}
+
+#[test]
+fn compile_test_5() {
+    let re: RegularExpression = RegularExpression::new(String::from("[a-z]+@[a-z]+\\.[a-z]{2,60}"));
+    let engine = re.compile(&DfaLimits::new()).unwrap();
+
+    assert!(matches!(engine, Engine::MinimizedDfa(_)));
+    assert!(engine.is_match("john@example.com"));
+}
+
+#[test]
+fn compile_test_6() {
+    // Too big for the Glushkov engine, so a DFA is tried first.
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){40}"));
+    let deadline = Instant::now() - Duration::from_secs(1);
+
+    assert_eq!(
+        re.compile(&DfaLimits::new().cancel_when(|| true))
+            .unwrap_err(),
+        Error::Dfa(DfaError::Cancelled)
+    );
+    assert_eq!(
+        re.compile(&DfaLimits::new().deadline(deadline))
+            .unwrap_err(),
+        Error::Dfa(DfaError::DeadlineExceeded)
+    );
+    assert!(DfaError::TooManyStates { limit: 1 }.is_size_limit());
+    assert!(!DfaError::Cancelled.is_size_limit());
+}
+
+#[test]
+fn dfa_limits_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){12}"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+
+    assert_eq!(
+        nfa.to_dfa_with_limits(&DfaLimits::new().max_states(100))
+            .unwrap_err(),
+        DfaError::TooManyStates { limit: 100 }
+    );
+}
+
+#[test]
+fn dfa_limits_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){12}"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+
+    assert_eq!(
+        nfa.to_dfa_with_limits(&DfaLimits::new().max_transitions(100))
+            .unwrap_err(),
+        DfaError::TooManyTransitions { limit: 100 }
+    );
+}
+
+#[test]
+fn dfa_limits_test_3() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*abb"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+    let deadline = Instant::now() - Duration::from_secs(1);
+
+    assert_eq!(
+        nfa.to_dfa_with_limits(&DfaLimits::new().deadline(deadline))
+            .unwrap_err(),
+        DfaError::DeadlineExceeded
+    );
+}
+
+#[test]
+fn dfa_limits_test_4() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*abb"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+
+    assert_eq!(
+        nfa.to_dfa_with_limits(&DfaLimits::new().cancel_when(|| true))
+            .unwrap_err(),
+        DfaError::Cancelled
+    );
+}
+
+#[test]
+fn dfa_limits_test_5() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*abb"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+    let dfa = nfa
+        .to_dfa_with_limits(&DfaLimits::new().max_states(10).max_transitions(20))
+        .unwrap();
+
+    assert!(dfa.is_match("aababb"));
+}
+
+#[test]
+fn dfa_limits_test_6() {
+    let re: RegularExpression = RegularExpression::new(String::from("\\d{1,100000}"));
+    let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+
+    assert_eq!(
+        epsilon_nfa
+            .to_nfa_with_limits(&DfaLimits::new().max_nfa_states(10000))
+            .unwrap_err(),
+        DfaError::TooManyNfaStates {
+            estimated: 100002,
+            limit: 10000
+        }
+    );
+}
+
+#[test]
+fn dfa_limits_test_7() {
+    // The budgets are checked as the DFA grows, so a single state cannot
+    // overshoot them.
+    let re: RegularExpression = RegularExpression::new(String::from("[a-z]"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+
+    assert_eq!(
+        nfa.to_dfa_with_limits(&DfaLimits::new().max_transitions(10))
+            .unwrap_err(),
+        DfaError::TooManyTransitions { limit: 10 }
+    );
+    assert_eq!(
+        nfa.to_dfa_with_limits(&DfaLimits::new().max_states(1))
+            .unwrap_err(),
+        DfaError::TooManyStates { limit: 1 }
+    );
+    assert!(
+        nfa.to_dfa_with_limits(&DfaLimits::new().max_states(2).max_transitions(26))
+            .is_ok()
+    );
+}
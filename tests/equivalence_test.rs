@@ -0,0 +1,105 @@
+use dumb_regex::regex::{
+    Comparison, Error, MinimizedDfa, RegularExpression, equivalent, equivalent_patterns, is_subset,
+    is_subset_patterns,
+};
+
+fn minimized_dfa(pattern: &str) -> MinimizedDfa {
+    RegularExpression::new(pattern.to_string())
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa()
+}
+
+#[test]
+fn equivalence_test_1() {
+    let refactored = [
+        ("(a|b)*", "(a*b*)*"),
+        ("a(ba)*", "(ab)*a"),
+        ("[0-9]+", "[0-9][0-9]*"),
+        ("(a|b)*abb", "(a|b)*a(bb)"),
+        ("x?x?", "x{0,2}"),
+    ];
+    for (left, right) in refactored {
+        assert_eq!(
+            equivalent(&minimized_dfa(left), &minimized_dfa(right)),
+            Comparison::Holds,
+            "{left} {right}"
+        );
+        assert_eq!(equivalent_patterns(left, right), Ok(Comparison::Holds));
+    }
+}
+
+#[test]
+fn equivalence_test_2() {
+    // The shortest string only one side accepts, and the smallest of those.
+    let changed = [
+        ("(a|b)*", "(a|b)+", ""),
+        ("[0-9]+", "[0-9]{1,3}", "0000"),
+        ("(a|b)*abb", "(a|b)*bb", "bb"),
+        ("a|b|c", "a|c", "b"),
+        ("ab|ba", "ba", "ab"),
+    ];
+    for (left, right, counterexample) in changed {
+        let expected = Comparison::Counterexample(counterexample.chars().collect::<Vec<char>>());
+        assert_eq!(
+            equivalent(&minimized_dfa(left), &minimized_dfa(right)),
+            expected
+        );
+        assert_eq!(
+            equivalent(&minimized_dfa(right), &minimized_dfa(left)),
+            expected
+        );
+        assert_eq!(
+            equivalent_patterns(left, right),
+            Ok(Comparison::Counterexample(counterexample.to_string()))
+        );
+    }
+}
+
+#[test]
+fn equivalence_test_3() {
+    assert_eq!(is_subset_patterns("a+", "a*"), Ok(Comparison::Holds));
+    assert_eq!(
+        is_subset_patterns("a*", "a+"),
+        Ok(Comparison::Counterexample(String::new()))
+    );
+    assert_eq!(is_subset_patterns("(ab)+", "(a|b)*"), Ok(Comparison::Holds));
+    assert_eq!(
+        is_subset_patterns("[a-z]{2,4}", "[a-y]+"),
+        Ok(Comparison::Counterexample("az".to_string()))
+    );
+    assert_eq!(
+        is_subset(&minimized_dfa("abc|abd"), &minimized_dfa("ab[a-c]")),
+        Comparison::Counterexample(vec!['a', 'b', 'd'])
+    );
+
+    assert!(matches!(
+        equivalent_patterns("(a", "a"),
+        Err(Error::Syntax(_))
+    ));
+    assert!(matches!(
+        is_subset_patterns("a", "b)"),
+        Err(Error::Syntax(_))
+    ));
+}
+
+#[test]
+fn equivalence_test_4() {
+    // Whole strings are compared, so anchoring a pattern changes nothing.
+    assert_eq!(equivalent_patterns("^a+$", "a+"), Ok(Comparison::Holds));
+    assert_eq!(
+        equivalent_patterns("^ab$|^ba$", "ab|ba"),
+        Ok(Comparison::Holds)
+    );
+    assert_eq!(
+        is_subset_patterns("^a*$", "a+"),
+        Ok(Comparison::Counterexample(String::new()))
+    );
+    assert!(!equivalent_patterns("^a", "b").unwrap().holds());
+    assert!(matches!(
+        equivalent_patterns("^a|b", "a|b"),
+        Err(Error::Syntax(_))
+    ));
+}
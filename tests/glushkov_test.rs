@@ -0,0 +1,51 @@
+use dumb_regex::regex::{RegularExpression, Searcher};
+
+#[test]
+fn position_count_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*abb"));
+    let glushkov = re.to_glushkov().unwrap();
+
+    assert_eq!(glushkov.position_count(), 5);
+}
+
+#[test]
+fn position_count_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("(ab){2,4}c?"));
+    let glushkov = re.to_glushkov().unwrap();
+
+    assert_eq!(glushkov.position_count(), 9);
+    assert!(glushkov.is_match("ababab"));
+    assert!(glushkov.is_match("ababababc"));
+    assert!(!glushkov.is_match("ab"));
+    assert!(!glushkov.is_match("ababababab"));
+}
+
+#[test]
+fn position_count_test_3() {
+    let re: RegularExpression = RegularExpression::new(String::from("[a-z]{63}"));
+    assert!(re.to_glushkov().is_ok());
+
+    let re: RegularExpression = RegularExpression::new(String::from("[a-z]{64}"));
+    assert!(re.to_glushkov().is_err());
+}
+
+#[test]
+fn unicode_class_test() {
+    let re: RegularExpression = RegularExpression::new(String::from("(é|[α-ω])+x"));
+    let glushkov = re.to_glushkov().unwrap();
+
+    assert!(glushkov.is_match("éλωx"));
+    assert!(!glushkov.is_match("éΛx"));
+}
+
+#[test]
+fn find_test() {
+    let re: RegularExpression = RegularExpression::new(String::from("\\d+(\\.\\d+)?"));
+    let glushkov = re.to_glushkov().unwrap();
+    let matches: Vec<&str> = glushkov
+        .find_iter("pi is 3.14, e is 2.718 and 42.")
+        .map(|found| found.as_str())
+        .collect();
+
+    assert_eq!(matches, vec!["3.14", "2.718", "42"]);
+}
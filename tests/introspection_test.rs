@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+
+use dumb_regex::regex::{Label, MinimizedDfa, Nfa, RegularExpression, State};
+
+// Runs a deterministic automaton over its transition list alone.
+fn run_edges(
+    edges: &[(usize, char, usize)],
+    start: usize,
+    accepting: &HashSet<usize>,
+    input: &str,
+) -> bool {
+    let mut curr = start;
+    for c in input.chars() {
+        match edges
+            .iter()
+            .find(|&&(from, symbol, _)| from == curr && symbol == c)
+        {
+            Some(&(_, _, to)) => curr = to,
+            None => return false,
+        }
+    }
+    accepting.contains(&curr)
+}
+
+#[test]
+fn introspection_test_1() {
+    let re = RegularExpression::new("(a|b)*abb".to_string());
+    let minimized_dfa: MinimizedDfa = re
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa();
+
+    // The four states of the textbook automaton, plus the dead state.
+    assert_eq!(minimized_dfa.state_count(), 5);
+    let accepting: Vec<usize> = minimized_dfa.accepting_states().collect();
+    assert_eq!(accepting.len(), 1);
+    assert!(minimized_dfa.is_accepting_state(accepting[0]));
+    assert!(!minimized_dfa.is_accepting_state(minimized_dfa.start()));
+
+    let edges: Vec<(usize, char, usize)> = minimized_dfa.transitions().collect();
+    assert_eq!(edges.len(), 8);
+    let accepting: HashSet<usize> = accepting.into_iter().collect();
+    for input in ["abb", "aabb", "babb", "ab", "abba", ""] {
+        assert_eq!(
+            run_edges(&edges, minimized_dfa.start(), &accepting, input),
+            minimized_dfa.is_match(input),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn introspection_test_2() {
+    let re = RegularExpression::new("ab|ac".to_string());
+    let nfa: Nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+    let dfa = nfa.to_dfa();
+
+    let edges: Vec<(usize, char, usize)> = nfa.transitions().collect();
+    let mut sorted = edges.clone();
+    sorted.sort_unstable();
+    assert_eq!(edges, sorted);
+    assert!(
+        edges
+            .iter()
+            .all(|&(from, _, to)| from < nfa.state_count() && to < nfa.state_count())
+    );
+    // Two `a` edges leave the start state, one per alternative.
+    let from_start = edges
+        .iter()
+        .filter(|&&(from, c, _)| from == nfa.start() && c == 'a');
+    assert_eq!(from_start.count(), 2);
+    assert!(
+        nfa.accepting_states()
+            .all(|state| nfa.is_accepting_state(state))
+    );
+
+    let accepting: HashSet<usize> = dfa.accepting_states().collect();
+    let edges: Vec<(usize, char, usize)> = dfa.transitions().collect();
+    assert!(dfa.state_count() >= 3);
+    for input in ["ab", "ac", "a", "abc", "bc"] {
+        assert_eq!(
+            run_edges(&edges, dfa.start(), &accepting, input),
+            dfa.is_match(input),
+            "{input}"
+        );
+    }
+    assert_eq!(dfa.next_state(dfa.start(), 'b'), None);
+}
+
+#[test]
+fn introspection_test_3() {
+    let re = RegularExpression::new("a[0-9]?|b{2,3}".to_string());
+    let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+    let edges: Vec<_> = epsilon_nfa.transitions().collect();
+
+    let accepting: Vec<usize> = epsilon_nfa.accepting_states().collect();
+    assert_eq!(accepting.len(), 1);
+    assert_eq!(epsilon_nfa.state(accepting[0]), &State::Match);
+    assert!(edges.iter().all(|&(from, _, _)| from != accepting[0]));
+
+    assert!(edges.iter().any(|(_, label, _)| *label == Label::Epsilon));
+    assert!(
+        edges
+            .iter()
+            .any(|(_, label, _)| *label == Label::Ranges(&['0'..='9']))
+    );
+    // The counted state loops on its own ranges.
+    let (counted, _, _) = edges
+        .iter()
+        .find(|(from, label, to)| from == to && *label == Label::Ranges(&['b'..='b']))
+        .unwrap();
+    assert!(matches!(
+        epsilon_nfa.state(*counted),
+        State::Counted {
+            min: 2,
+            max: Some(3),
+            ..
+        }
+    ));
+    assert!(
+        edges.iter().all(
+            |&(from, _, to)| from < epsilon_nfa.state_count() && to < epsilon_nfa.state_count()
+        )
+    );
+}
@@ -0,0 +1,86 @@
+use dumb_regex::regex::{Automaton, MinimizedDfa, Nfa, RegularExpression};
+
+fn nfa(pattern: &str) -> Nfa {
+    RegularExpression::new(pattern.to_string())
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+}
+
+fn minimized_dfa(pattern: &str) -> MinimizedDfa {
+    nfa(pattern).to_dfa().to_minimized_dfa()
+}
+
+const INPUTS: [&str; 12] = [
+    "", "a", "b", "ab", "ba", "abc", "abab", "aab", "abb", "cc", "abcab", "bab",
+];
+
+fn assert_same_language<A, B>(left: &A, right: &B)
+where
+    A: Automaton<Symbol = char>,
+    B: Automaton<Symbol = char>,
+{
+    for input in INPUTS {
+        assert_eq!(
+            left.accepts(input.chars()),
+            right.accepts(input.chars()),
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn kleene_test_1() {
+    let ab = nfa("ab");
+    let c = nfa("c*");
+
+    assert_same_language(&ab.union(&c), &nfa("ab|c*"));
+    assert_same_language(&ab.concat(&c), &nfa("abc*"));
+    assert_same_language(&c.concat(&ab), &nfa("c*ab"));
+    assert_same_language(&ab.star(), &nfa("(ab)*"));
+    assert_same_language(&ab.plus(), &nfa("(ab)+"));
+    assert_same_language(&ab.optional(), &nfa("(ab)?"));
+}
+
+#[test]
+fn kleene_test_2() {
+    // The start state of (a|b)*b is reached again, so starring it must not
+    // just mark that state accepting.
+    let loops = nfa("(a|b)*b");
+    assert_same_language(&loops.star(), &nfa("((a|b)*b)*"));
+    assert_same_language(&loops.optional(), &nfa("((a|b)*b)?"));
+    assert!(!loops.optional().accepts("a".chars()));
+
+    let dfa = loops.to_dfa();
+    let other = nfa("a|c").to_dfa();
+    assert_same_language(&dfa.union(&other), &nfa("(a|b)*b|a|c"));
+    assert_same_language(&dfa.concat(&other), &nfa("(a|b)*b(a|c)"));
+    assert_same_language(&dfa.star(), &nfa("((a|b)*b)*"));
+    assert_same_language(&dfa.plus(), &nfa("((a|b)*b)+"));
+    assert_same_language(&dfa.optional(), &nfa("((a|b)*b)?"));
+    assert!(dfa.concat(&other).is_match("abba"));
+    assert!(!dfa.concat(&other).is_match("abbb"));
+}
+
+#[test]
+fn kleene_test_3() {
+    let ab = minimized_dfa("ab");
+    let b = minimized_dfa("b+");
+
+    // The minimal DFA is unique, so a composed one has exactly as many states
+    // as the one compiled from the pattern.
+    let cases = [
+        (ab.union(&b), minimized_dfa("ab|b+")),
+        (ab.concat(&b), minimized_dfa("abb+")),
+        (ab.star(), minimized_dfa("(ab)*")),
+        (ab.plus(), minimized_dfa("(ab)+")),
+        (b.optional(), minimized_dfa("b*")),
+        (ab.union(&b).star().concat(&ab), minimized_dfa("(ab|b+)*ab")),
+    ];
+    for (composed, expected) in cases {
+        assert_same_language(&composed, &expected);
+        assert_eq!(composed.state_count(), expected.state_count());
+    }
+    assert!(ab.plus().is_match("abab"));
+    assert!(!ab.plus().is_match("aba"));
+}
@@ -0,0 +1,131 @@
+use dumb_regex::regex::{RegularExpression, ReverseSearcher, Searcher};
+
+#[test]
+fn exponential_blowup_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){20}"));
+    let lazy_dfa = re.to_epsilon_nfa().unwrap().to_nfa().to_lazy_dfa();
+
+    assert!(lazy_dfa.is_match(&format!("{}a{}", "ab".repeat(50), "b".repeat(20))));
+}
+
+#[test]
+fn exponential_blowup_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){20}"));
+    let lazy_dfa = re.to_epsilon_nfa().unwrap().to_nfa().to_lazy_dfa();
+
+    assert!(!lazy_dfa.is_match(&format!("{}b{}", "ab".repeat(50), "b".repeat(20))));
+}
+
+#[test]
+fn cache_capacity_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){20}"));
+    let lazy_dfa = re
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_lazy_dfa_with_cache_capacity(8);
+
+    let mut cache = lazy_dfa.create_cache();
+
+    assert!(lazy_dfa.is_match_with_cache(
+        &mut cache,
+        &format!("{}a{}", "abba".repeat(100), "ab".repeat(10))
+    ));
+    assert!(cache.state_count() <= 8);
+}
+
+#[test]
+fn cache_capacity_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){20}"));
+    let lazy_dfa = re
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_lazy_dfa_with_cache_capacity(8);
+
+    assert!(!lazy_dfa.is_match(&format!("{}b{}", "abba".repeat(100), "ab".repeat(10))));
+    assert!(lazy_dfa.is_match("a".repeat(21).as_str()));
+}
+
+#[test]
+fn cache_reuse_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*abb"));
+    let lazy_dfa = re.to_epsilon_nfa().unwrap().to_nfa().to_lazy_dfa();
+    let mut cache = lazy_dfa.create_cache();
+
+    assert!(lazy_dfa.is_match_with_cache(&mut cache, "ababb"));
+    let state_count = cache.state_count();
+    assert!(state_count > 0);
+    assert!(!lazy_dfa.is_match_with_cache(&mut cache, "abab"));
+    assert_eq!(cache.state_count(), state_count);
+    assert!(lazy_dfa.is_match_with_cache(&mut cache, "bbabb"));
+}
+
+#[test]
+fn find_test_1() {
+    // States are let go of once the cache is full, but never the ones a
+    // search is still in.
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){20}"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+    let haystack = format!("{}c{}", "abba".repeat(200), "ba".repeat(30));
+    let expected: Vec<(usize, usize)> = nfa
+        .find_iter(&haystack)
+        .map(|found| (found.start(), found.end()))
+        .collect();
+
+    for cache_capacity in [1, 8, 4096] {
+        let lazy_dfa = nfa.to_lazy_dfa_with_cache_capacity(cache_capacity);
+        let found: Vec<(usize, usize)> = lazy_dfa
+            .find_iter(&haystack)
+            .map(|found| (found.start(), found.end()))
+            .collect();
+        assert_eq!(found, expected, "{cache_capacity}");
+        assert_eq!(
+            lazy_dfa.rfind(&haystack).map(|found| found.range()),
+            nfa.rfind(&haystack).map(|found| found.range())
+        );
+    }
+    assert_eq!(expected, vec![(0, 800), (801, 861)]);
+}
+
+#[test]
+fn find_test_2() {
+    // A search that keeps filling the cache gives up on it and finishes on the
+    // NFA, which leaves the cache empty.
+    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*a(a|b){20}"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+    let lazy_dfa = nfa.to_lazy_dfa_with_cache_capacity(8);
+    let haystack = format!("{}c{}", "abba".repeat(200), "ba".repeat(30));
+    let mut cache = lazy_dfa.create_cache();
+
+    assert_eq!(
+        lazy_dfa
+            .find_at_with_cache(&mut cache, &haystack, 0)
+            .map(|found| found.range()),
+        Some(0..800)
+    );
+    assert_eq!(cache.state_count(), 0);
+    assert_eq!(
+        lazy_dfa
+            .find_at_with_cache(&mut cache, &haystack, 800)
+            .map(|found| found.range()),
+        nfa.find_at(&haystack, 800).map(|found| found.range())
+    );
+}
+
+#[test]
+#[should_panic(expected = "the lazy DFA cache was created by another lazy DFA")]
+fn cache_owner_test_1() {
+    // Both NFAs have the same number of states.
+    let nfa = RegularExpression::new(String::from("ab"))
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa();
+    let other = RegularExpression::new(String::from("ba"))
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa();
+    let mut cache = other.to_lazy_dfa().create_cache();
+
+    nfa.to_lazy_dfa().is_match_with_cache(&mut cache, "ab");
+}
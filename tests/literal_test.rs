@@ -0,0 +1,101 @@
+use dumb_regex::regex::{AhoCorasick, DfaLimits, Engine, Literal, RegularExpression, Searcher};
+
+fn compile(pattern: &str) -> Engine {
+    RegularExpression::new(String::from(pattern))
+        .compile(&DfaLimits::new())
+        .unwrap()
+}
+
+fn find_all<S: Searcher>(searcher: &S, haystack: &str) -> Vec<(usize, usize)> {
+    searcher
+        .find_iter(haystack)
+        .map(|found| (found.start(), found.end()))
+        .collect()
+}
+
+#[test]
+fn literal_engine_test_1() {
+    let engine = compile("hello");
+
+    assert!(matches!(engine, Engine::Literal(_)));
+    assert!(engine.is_match("hello"));
+    assert!(!engine.is_match("hello!"));
+    assert!(!engine.is_match("hell"));
+}
+
+#[test]
+fn literal_engine_test_2() {
+    let engine = compile("foo|bar|(baz|qux)");
+
+    assert!(matches!(engine, Engine::LiteralSet(_)));
+    assert!(engine.is_match("foo"));
+    assert!(engine.is_match("qux"));
+    assert!(!engine.is_match("fo"));
+    assert!(!engine.is_match("foobar"));
+}
+
+#[test]
+fn literal_engine_test_3() {
+    assert!(matches!(compile("a(b|c)"), Engine::Glushkov(_)));
+    assert!(matches!(compile("foo|ba+r"), Engine::Glushkov(_)));
+    assert!(matches!(compile("\\.\\*"), Engine::Literal(_)));
+}
+
+#[test]
+fn literal_find_test() {
+    let literal = Literal::new("abab");
+
+    assert_eq!(literal.as_str(), "abab");
+    assert_eq!(
+        find_all(&literal, "ababababxabab"),
+        vec![(0, 4), (4, 8), (9, 13)]
+    );
+    assert_eq!(
+        find_all(&Literal::new(""), "ab"),
+        vec![(0, 0), (1, 1), (2, 2)]
+    );
+}
+
+#[test]
+fn aho_corasick_find_test_1() {
+    let aho_corasick = AhoCorasick::new(["he", "she", "his", "hers"]);
+
+    assert_eq!(aho_corasick.pattern_count(), 4);
+    assert_eq!(find_all(&aho_corasick, "ushers"), vec![(1, 4)]);
+    assert_eq!(find_all(&aho_corasick, "hishers"), vec![(0, 3), (3, 7)]);
+}
+
+#[test]
+fn aho_corasick_find_test_2() {
+    let aho_corasick = AhoCorasick::new(["abcd", "bc", "b"]);
+
+    assert_eq!(find_all(&aho_corasick, "xabcx"), vec![(2, 4)]);
+    assert_eq!(find_all(&aho_corasick, "xabcdx"), vec![(1, 5)]);
+}
+
+#[test]
+fn aho_corasick_find_test_3() {
+    let aho_corasick = AhoCorasick::new(["", "é"]);
+
+    assert!(aho_corasick.is_match(""));
+    assert_eq!(find_all(&aho_corasick, "aéb"), vec![(0, 0), (1, 3), (4, 4)]);
+}
+
+#[test]
+fn aho_corasick_agrees_with_dfa_test() {
+    let aho_corasick = AhoCorasick::new(["ab", "abc", "bcd", "cd", "d"]);
+    let dfa = RegularExpression::new(String::from("ab|abc|bcd|cd|d"))
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa();
+
+    for haystack in ["abcd", "xabcdx", "bcdab", "ddcabd", "aabbccdd"] {
+        assert_eq!(
+            find_all(&aho_corasick, haystack),
+            find_all(&dfa, haystack),
+            "{haystack}"
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use dumb_regex::regex::{DfaLimits, Engine, EngineKind, MatchKind, RegularExpression, Searcher};
+
+const PATTERNS: [&str; 6] = [
+    "(a|ab)(c|b)?",
+    "(sam|samwise)x?",
+    "\\d{2,4}",
+    "a*",
+    "(a|b)*abb",
+    "x(yz|y)*",
+];
+
+const HAYSTACK: &str = "abc samwise 12345 aab abb ababb xyzyyz";
+
+fn find_all<S: Searcher>(searcher: &S, haystack: &str) -> Vec<(usize, usize)> {
+    searcher
+        .find_iter(haystack)
+        .map(|found| (found.start(), found.end()))
+        .collect()
+}
+
+fn find_str(pattern: &str, match_kind: MatchKind, haystack: &str) -> Option<String> {
+    let re = RegularExpression::new(pattern.to_string());
+    let engine = re
+        .compile_with_match_kind(&DfaLimits::new(), match_kind)
+        .unwrap();
+    engine
+        .find(haystack)
+        .map(|found| found.as_str().to_string())
+}
+
+#[test]
+fn match_kind_test_1() {
+    let find = |match_kind| find_str("(a|ab)(c|b)?", match_kind, "abc");
+    assert_eq!(find(MatchKind::LeftmostFirst).as_deref(), Some("ab"));
+    assert_eq!(find(MatchKind::LeftmostLongest).as_deref(), Some("abc"));
+    assert_eq!(find(MatchKind::Earliest).as_deref(), Some("a"));
+}
+
+#[test]
+fn match_kind_test_2() {
+    // Literal sets prefer the alternative given first.
+    let find = |pattern, match_kind| find_str(pattern, match_kind, "samwise");
+    assert_eq!(
+        find("sam|samwise", MatchKind::LeftmostFirst).as_deref(),
+        Some("sam")
+    );
+    assert_eq!(
+        find("samwise|sam", MatchKind::LeftmostFirst).as_deref(),
+        Some("samwise")
+    );
+    assert_eq!(
+        find("samwise|sam", MatchKind::LeftmostLongest).as_deref(),
+        Some("samwise")
+    );
+    assert_eq!(
+        find("samwise|sam", MatchKind::Earliest).as_deref(),
+        Some("sam")
+    );
+}
+
+#[test]
+fn match_kind_test_3() {
+    // Repetitions are greedy under leftmost-first.
+    let find = |match_kind| find_str("\\d{2,4}", match_kind, "a12345");
+    assert_eq!(find(MatchKind::LeftmostFirst).as_deref(), Some("1234"));
+    assert_eq!(find(MatchKind::LeftmostLongest).as_deref(), Some("1234"));
+    assert_eq!(find(MatchKind::Earliest).as_deref(), Some("12"));
+}
+
+#[test]
+fn match_kind_test_4() {
+    let re = RegularExpression::new("(a|ab)(c|b)?".to_string());
+    let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+    let nfa = epsilon_nfa.to_nfa();
+    assert_eq!(
+        nfa.clone()
+            .with_match_kind(MatchKind::LeftmostFirst)
+            .unwrap_err()
+            .engine,
+        EngineKind::Nfa
+    );
+    assert_eq!(
+        nfa.to_dfa()
+            .to_minimized_dfa()
+            .with_match_kind(MatchKind::LeftmostFirst)
+            .unwrap_err()
+            .engine,
+        EngineKind::MinimizedDfa
+    );
+    assert_eq!(
+        re.to_glushkov()
+            .unwrap()
+            .with_match_kind(MatchKind::LeftmostFirst)
+            .unwrap_err()
+            .engine,
+        EngineKind::Glushkov
+    );
+    assert_eq!(
+        epsilon_nfa
+            .with_match_kind(MatchKind::LeftmostFirst)
+            .match_kind(),
+        MatchKind::LeftmostFirst
+    );
+}
+
+#[test]
+fn match_kind_test_5() {
+    // Every engine reports the same matches for the kinds it supports.
+    for pattern in PATTERNS {
+        let re = RegularExpression::new(pattern.to_string());
+        for match_kind in [MatchKind::LeftmostLongest, MatchKind::Earliest] {
+            let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+            let nfa = epsilon_nfa.to_nfa();
+            let engines = [
+                Engine::MinimizedDfa(
+                    nfa.to_dfa()
+                        .to_minimized_dfa()
+                        .with_match_kind(match_kind)
+                        .unwrap(),
+                ),
+                Engine::LazyDfa(
+                    nfa.to_lazy_dfa_with_cache_capacity(4)
+                        .with_match_kind(match_kind)
+                        .unwrap(),
+                ),
+                Engine::Nfa(nfa.with_match_kind(match_kind).unwrap()),
+                Engine::Glushkov(
+                    re.to_glushkov()
+                        .unwrap()
+                        .with_match_kind(match_kind)
+                        .unwrap(),
+                ),
+            ];
+            let expected = find_all(&epsilon_nfa.with_match_kind(match_kind), HAYSTACK);
+            for engine in &engines {
+                assert_eq!(
+                    find_all(engine, HAYSTACK),
+                    expected,
+                    "{pattern} {match_kind:?} {engine:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn match_kind_test_6() {
+    let re = RegularExpression::new("x(yz|y)*".to_string());
+    let engine = re
+        .compile_with_match_kind(&DfaLimits::new(), MatchKind::LeftmostFirst)
+        .unwrap();
+    assert!(matches!(engine, Engine::EpsilonNfa(_)));
+    assert!(engine.is_match("xyzy"));
+    assert_eq!(find_all(&engine, "xyzyyz xyy"), vec![(0, 6), (7, 10)]);
+}
+
+#[test]
+fn match_kind_test_7() {
+    let re = RegularExpression::new("a*".to_string());
+    let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+    let earliest = epsilon_nfa.clone().with_match_kind(MatchKind::Earliest);
+    assert_eq!(find_all(&earliest, "aa"), vec![(0, 0), (1, 1), (2, 2)]);
+    let first = epsilon_nfa.with_match_kind(MatchKind::LeftmostFirst);
+    assert_eq!(find_all(&first, "baa"), vec![(0, 0), (1, 3)]);
+}
+
+#[test]
+fn match_kind_test_8() {
+    // A match starting further left wins even when a later one ends first,
+    // and a haystack with no match is scanned once rather than from every
+    // position.
+    let no_match = format!("{}b", "a".repeat(50_000));
+    let cases = [
+        ("abcd|c", "xabcd", Some((1, 5))),
+        ("a+c", no_match.as_str(), None),
+        ("(a|b)*abb", "babababba", Some((0, 8))),
+    ];
+    for (pattern, haystack, expected) in cases {
+        let re = RegularExpression::new(pattern.to_string());
+        let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+        let engines = [
+            Engine::Dfa(nfa.to_dfa()),
+            Engine::MinimizedDfa(nfa.to_dfa().to_minimized_dfa()),
+            Engine::Nfa(nfa),
+            Engine::Glushkov(re.to_glushkov().unwrap()),
+        ];
+        for engine in &engines {
+            let found = engine
+                .find(haystack)
+                .map(|found| (found.start(), found.end()));
+            assert_eq!(found, expected, "{pattern} {engine:?}");
+        }
+    }
+}
+
+#[test]
+fn match_kind_test_9() {
+    // An earliest search reports the match ending first, even when another
+    // one starts further left.
+    let cases = [
+        ("abcd|c", Some("c")),
+        ("abcd|bc|c", Some("bc")),
+        ("b+c+", Some("bc")),
+        ("x", None),
+    ];
+    for (pattern, expected) in cases {
+        assert_eq!(
+            find_str(pattern, MatchKind::Earliest, "abcd").as_deref(),
+            expected,
+            "{pattern}"
+        );
+        let re = RegularExpression::new(pattern.to_string());
+        let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+        let nfa = epsilon_nfa.to_nfa();
+        let engines = [
+            Engine::EpsilonNfa(epsilon_nfa.with_match_kind(MatchKind::Earliest)),
+            Engine::Dfa(nfa.to_dfa().with_match_kind(MatchKind::Earliest).unwrap()),
+            Engine::MinimizedDfa(
+                nfa.to_dfa()
+                    .to_minimized_dfa()
+                    .with_match_kind(MatchKind::Earliest)
+                    .unwrap(),
+            ),
+            Engine::Nfa(nfa.with_match_kind(MatchKind::Earliest).unwrap()),
+            Engine::Glushkov(
+                re.to_glushkov()
+                    .unwrap()
+                    .with_match_kind(MatchKind::Earliest)
+                    .unwrap(),
+            ),
+        ];
+        for engine in &engines {
+            let found = engine.find("abcd").map(|found| found.as_str());
+            assert_eq!(found, expected, "{pattern} {engine:?}");
+        }
+    }
+}
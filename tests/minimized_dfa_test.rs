@@ -1,21 +1,28 @@
-use dumb_regex::regex::RegularExpression;
+use dumb_regex::regex::{RegularExpression, Searcher};
 
 #[test]
-fn basic_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("a"));
+fn accelerated_state_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from(".*foo"));
     let minimized_dfa = re
         .to_epsilon_nfa()
         .unwrap()
         .to_nfa()
         .to_dfa()
         .to_minimized_dfa();
+    let haystack = "x".repeat(100000) + "foo";
 
-    assert!(minimized_dfa.is_match("a"));
+    assert!(
+        (0..minimized_dfa.state_count())
+            .filter_map(|state| minimized_dfa.accelerator(state))
+            .any(|accelerator| accelerator.exits() == b"f")
+    );
+    assert!(minimized_dfa.is_match(&haystack));
+    assert!(!minimized_dfa.is_match(&(haystack + "x")));
 }
 
 #[test]
-fn basic_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("a"));
+fn accelerated_state_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("a[^\"]*\"b"));
     let minimized_dfa = re
         .to_epsilon_nfa()
         .unwrap()
@@ -23,806 +30,56 @@ fn basic_test_2() {
         .to_dfa()
         .to_minimized_dfa();
 
-    assert!(!minimized_dfa.is_match("b"));
+    assert!(minimized_dfa.is_match("a0123456789 é abcdefgh\"b"));
+    assert!(!minimized_dfa.is_match("a0123456789 € abcdefgh\"b"));
+    assert!(!minimized_dfa.is_match("a0123456789abcdefgh\"\"b"));
 }
 
 #[test]
-fn basic_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("a"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("ab"));
-}
-
-#[test]
-fn star_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("a*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("aaaaaaaaaaa"));
-}
-
-#[test]
-fn star_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("a*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("aaaaaaaaaabaaaaaa"));
-}
-
-#[test]
-fn union_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("a|b|c"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("a"));
-}
-
-#[test]
-fn union_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("a|b|c"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("b"));
-}
-
-#[test]
-fn union_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("a|b|c"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("d"));
-}
-
-#[test]
-fn char_set_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("[hc]at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("hat"));
-}
-
-#[test]
-fn char_set_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("[hc]at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("cat"));
-}
-
-#[test]
-fn char_set_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("[hc]at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("mat"));
-}
-
-#[test]
-fn any_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from(".at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("hat"));
-}
-
-#[test]
-fn any_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from(".at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("cat"));
-}
-
-#[test]
-fn any_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from(".at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("mat"));
-}
-
-#[test]
-fn any_test_4() {
-    let re: RegularExpression = RegularExpression::new(String::from(".at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("pat"));
-}
-
-#[test]
-fn group_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("([hc]at)?[mp]at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("mat"));
-}
-
-#[test]
-fn group_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("([hc]at)?[mp]at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("hat"));
-}
-
-#[test]
-fn group_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("([hc]at)?[mp]at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("pat"));
-}
-
-#[test]
-fn group_test_4() {
-    let re: RegularExpression = RegularExpression::new(String::from("([hc]at)?[mp]at"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("catmat"));
-}
-
-#[test]
-fn set_range_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("[a-zA-Z0-9]"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("5"));
-}
-
-#[test]
-fn set_range_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("[a-zA-Z0-9]"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("G"));
-}
-
-#[test]
-fn set_range_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("[a-zA-Z0-9]"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("@"));
-}
-
-#[test]
-fn negated_set_range_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("[^a-zA-Z0-9]"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("5"));
-}
-
-#[test]
-fn negated_set_range_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("[^a-zA-Z0-9]"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("G"));
-}
-
-#[test]
-fn negated_set_range_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("[^a-zA-Z0-9]"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("@"));
-}
-
-#[test]
-fn special_char_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("\\w*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("0123"));
-}
-#[test]
-fn special_char_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("\\w*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("ZYX"));
-}
-
-#[test]
-fn special_char_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("\\w*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("abcd"));
-}
-
-#[test]
-fn special_char_test_4() {
-    let re: RegularExpression = RegularExpression::new(String::from("\\w*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("abcdef_ABCDEF___01234"));
-}
-
-#[test]
-fn special_char_test_5() {
-    let re: RegularExpression = RegularExpression::new(String::from("\\w*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("0+1-2"));
-}
-
-#[test]
-fn numeral_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("1"));
-}
-
-#[test]
-fn numeral_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("1000000"));
-}
-
-#[test]
-fn numeral_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("-1"));
-}
-
-#[test]
-fn numeral_test_4() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("1e9"));
-}
-
-#[test]
-fn numeral_test_5() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("1e-5"));
-}
-
-#[test]
-fn numeral_test_6() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("1E-5"));
-}
-
-#[test]
-fn numeral_test_7() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("1e-12233342"));
-}
-
-#[test]
-fn numeral_test_8() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("3.1415926535"));
-}
-
-#[test]
-fn numeral_test_9() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("237429342e24801"));
-}
-
-#[test]
-fn numeral_test_10() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("6.022e+23"));
-}
-
-#[test]
-fn numeral_test_11() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("e+23"));
-}
-
-#[test]
-fn numeral_test_12() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("abcd"));
-}
-
-#[test]
-fn numeral_test_13() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("abcd123"));
-}
-
-#[test]
-fn numeral_test_14() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?",
-    ));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("123abcd"));
-}
-
-#[test]
-fn ab_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b)*abb(a|b)*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("aaaabbbbbb"));
-}
-
-#[test]
-fn easy_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a*|b*)*"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match(""));
-}
-
-#[test]
-fn counted_repetition_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){0}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match(""));
-}
-
-#[test]
-fn counted_repetition_test_2() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){0,0}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match(""));
-}
-
-#[test]
-fn counted_repetition_test_3() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){0,0}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("a"));
-}
-
-#[test]
-fn counted_repetition_test_4() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){0,1}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match(""));
-}
-
-#[test]
-fn counted_repetition_test_5() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){0,1}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("a"));
-}
-
-#[test]
-fn counted_repetition_test_6() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){0,1}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("ab"));
-}
-
-#[test]
-fn counted_repetition_test_7() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2,4}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match(""));
-}
-
-#[test]
-fn counted_repetition_test_8() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2,4}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("a"));
-}
-
-#[test]
-fn counted_repetition_test_9() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2,4}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("ba"));
-}
-
-#[test]
-fn counted_repetition_test_10() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2,4}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("aba"));
-}
-
-#[test]
-fn counted_repetition_test_11() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2,4}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("aaba"));
-}
-
-#[test]
-fn counted_repetition_test_12() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2,4}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(!minimized_dfa.is_match("abbaa"));
-}
-
-#[test]
-fn counted_repetition_test_13() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2,}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
-
-    assert!(minimized_dfa.is_match("aaaaaaaaaaaa"));
-}
-
-#[test]
-fn counted_repetition_test_14() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2}"));
-    let minimized_dfa = re
-        .to_epsilon_nfa()
-        .unwrap()
-        .to_nfa()
-        .to_dfa()
-        .to_minimized_dfa();
+fn accelerated_state_test_3() {
+    let re: RegularExpression = RegularExpression::new(String::from("x.*y"));
+    let dfa = re.to_epsilon_nfa().unwrap().to_nfa().to_dfa();
+    let minimized_dfa = dfa.to_minimized_dfa();
+    let matches: Vec<&str> = minimized_dfa
+        .find_iter("ax12345678yyb xzzzzzzzzzzzzzzz")
+        .map(|found| found.as_str())
+        .collect();
 
-    assert!(!minimized_dfa.is_match("a"));
+    assert_eq!(matches, vec!["x12345678yy"]);
+    for input in [
+        "xy",
+        "x0123456789y",
+        "x0123456789",
+        "xéééééééééy",
+        "x\nabcdefghiy",
+    ] {
+        assert_eq!(
+            minimized_dfa.is_match(input),
+            dfa.is_match(input),
+            "{input}"
+        );
+    }
 }
 
 #[test]
-fn counted_repetition_test_15() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){2}"));
+fn parallel_match_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("((\\d+,)*\\d+\n)*"));
     let minimized_dfa = re
         .to_epsilon_nfa()
         .unwrap()
         .to_nfa()
         .to_dfa()
         .to_minimized_dfa();
+    let input = "12,345,6\n7\n".repeat(20000);
 
-    assert!(!minimized_dfa.is_match("abb"));
+    assert!(minimized_dfa.is_match_parallel(&input, 8));
+    assert!(!minimized_dfa.is_match_parallel(&(input.clone() + "1,"), 8));
+    assert!(!minimized_dfa.is_match_parallel(&input.replacen("7\n", "7,\n", 1), 8));
 }
 
 #[test]
-fn counted_repetition_test_16() {
-    let re: RegularExpression = RegularExpression::new(String::from("(a|b){10,10}"));
+fn parallel_match_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("(é|ab)*"));
     let minimized_dfa = re
         .to_epsilon_nfa()
         .unwrap()
@@ -830,14 +87,25 @@ fn counted_repetition_test_16() {
         .to_dfa()
         .to_minimized_dfa();
 
-    assert!(!minimized_dfa.is_match("abaaa"));
+    for len in [0, 1, 4095, 40000, 40001] {
+        let input = "éab".repeat(len);
+        for threads in [1, 2, 3, 7] {
+            assert_eq!(
+                minimized_dfa.is_match_parallel(&input, threads),
+                minimized_dfa.is_match(&input),
+                "{len} {threads}"
+            );
+            let broken = input.clone() + "a";
+            assert!(!minimized_dfa.is_match_parallel(&broken, threads));
+        }
+    }
 }
 
 #[test]
-fn email_test_1() {
-    let re: RegularExpression = RegularExpression::new(String::from(
-        "[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\\.[a-zA-Z]{2,}",
-    ));
+fn non_latin1_test_1() {
+    // Minimization used to try only the chars up to U+00FF, so states told
+    // apart only by other chars were merged.
+    let re: RegularExpression = RegularExpression::new(String::from("λ+|日本|本日"));
     let minimized_dfa = re
         .to_epsilon_nfa()
         .unwrap()
@@ -845,5 +113,10 @@ fn email_test_1() {
         .to_dfa()
         .to_minimized_dfa();
 
-    assert!(minimized_dfa.is_match("john.smith@example.com"));
+    assert!(minimized_dfa.is_match("λλλ"));
+    assert!(minimized_dfa.is_match("日本"));
+    assert!(minimized_dfa.is_match("本日"));
+    assert!(!minimized_dfa.is_match("日日"));
+    assert!(!minimized_dfa.is_match("本本"));
+    assert!(!minimized_dfa.is_match("λ日"));
 }
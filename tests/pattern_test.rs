@@ -0,0 +1,139 @@
+use dumb_regex::regex::{
+    Ast, Pattern, Regex, RegularExpression, Searcher, alt, class, escape, lit, seq,
+};
+
+fn parse(pattern: &str) -> Ast {
+    RegularExpression::new(pattern.to_string())
+        .to_ast()
+        .unwrap()
+}
+
+#[test]
+fn pattern_test_1() {
+    assert_eq!(lit("GET"), parse("GET"));
+    assert_eq!(lit("x"), parse("x"));
+    assert_eq!(class(['a'..='z', '0'..='9']), parse("[a-z0-9]"));
+    assert_eq!(seq![lit("ab"), lit("c")], parse("(ab)c"));
+    assert_eq!(
+        alt![lit("GET"), lit("PUT"), lit("POST")],
+        parse("GET|PUT|POST")
+    );
+    assert_eq!(alt![alt![lit("a"), lit("b")], lit("c")], parse("(a|b)|c"));
+}
+
+#[test]
+fn pattern_test_2() {
+    assert_eq!(lit("a").repeat(2..=5), parse("a{2,5}"));
+    assert_eq!(lit("a").repeat(2..6), parse("a{2,5}"));
+    assert_eq!(lit("a").repeat(3..), parse("a{3,}"));
+    assert_eq!(lit("a").repeat(..=4), parse("a{0,4}"));
+    assert_eq!(lit("a").repeat(..), parse("a*"));
+    assert_eq!(lit("a").repeat(1..), parse("a+"));
+    assert_eq!(lit("ab").optional(), parse("(ab)?"));
+    assert_eq!(lit("ab").capture("name"), parse("(?<name>ab)"));
+}
+
+#[test]
+fn pattern_test_3() {
+    // GET /items/<id> with an optional query string.
+    let id = class(['0'..='9']).repeat(1..);
+    let ast = seq![
+        alt![lit("GET"), lit("HEAD")],
+        lit(" /items/"),
+        id.capture("id"),
+        seq![lit("?"), class(['a'..='z', '='..='=']).repeat(1..)].optional(),
+    ];
+    assert_eq!(ast, parse("(GET|HEAD) /items/(?<id>[0-9]+)(\\?[=a-z]+)?"));
+
+    let minimized_dfa = ast
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa();
+    assert!(minimized_dfa.is_match("HEAD /items/42?sort=asc"));
+    assert!(!minimized_dfa.is_match("POST /items/42"));
+}
+
+#[test]
+fn pattern_test_4() {
+    let user_data = "a.b*(c)|[d]{1}^$+?\\";
+    let escaped = escape(user_data);
+    assert_eq!(escaped, "a\\.b\\*\\(c\\)\\|\\[d\\]\\{1\\}\\^\\$\\+\\?\\\\");
+    assert_eq!(parse(&escaped), lit(user_data));
+
+    let re = Regex::new(&format!("^{}$", escape("1+1=2?"))).unwrap();
+    assert!(re.is_match("1+1=2?"));
+    assert!(!re.is_match("11=2"));
+    assert_eq!(escape("plain text"), "plain text");
+}
+
+#[test]
+fn pattern_test_5() {
+    // The name is kept in the IR, but a named group matches what it brackets.
+    let ast = lit("ab").capture("pair");
+    assert_eq!(
+        ast,
+        Ast::Capture {
+            name: "pair".to_string(),
+            inner: Box::new(lit("ab")),
+        }
+    );
+    assert_eq!(parse("(?<empty>)"), lit("").capture("empty"));
+    assert!(
+        RegularExpression::new("(?<>a)".to_string())
+            .to_ast()
+            .is_err()
+    );
+    assert!(
+        RegularExpression::new("(?<a-b>a)".to_string())
+            .to_ast()
+            .is_err()
+    );
+
+    let re = Regex::new("x(?<word>a|bc)+y").unwrap();
+    assert_eq!(re.find("__xabcay").map(|m| m.as_str()), Some("xabcay"));
+
+    // Literals of other symbols than chars.
+    assert_eq!(lit([1u8, 2]), seq![Ast::literal(1u8), Ast::literal(2)]);
+    assert_eq!(lit(vec![7u8]), Ast::literal(7u8));
+    assert_eq!(
+        lit(&b"hi"[..]),
+        seq![Ast::literal(b'h'), Ast::literal(b'i')]
+    );
+}
+
+#[test]
+#[should_panic(expected = "repeat range starts at 5 but ends at 2")]
+fn pattern_test_6() {
+    let (min, max) = (5, 3);
+    lit("a").repeat(min..max);
+}
+
+#[test]
+#[should_panic(expected = "repeat range starts after the largest count")]
+fn pattern_test_7() {
+    use std::ops::Bound;
+
+    lit("a").repeat((Bound::Excluded(usize::MAX), Bound::Unbounded));
+}
+
+#[test]
+#[should_panic(expected = "repeat range ends before the smallest count")]
+fn pattern_test_8() {
+    lit("a").repeat(..0);
+}
+
+#[test]
+fn pattern_test_9() {
+    // No alternatives leave nothing to match.
+    let nothing: Ast = alt![];
+    assert!(nothing.prefilter().is_none());
+    assert!(!nothing.to_glushkov().unwrap().is_match(""));
+    assert!(!nothing.to_epsilon_nfa().unwrap().is_match("a"));
+    assert!(!nothing.reverse().to_glushkov().unwrap().is_match(""));
+    assert_eq!(
+        seq![lit("a"), alt![]].to_glushkov().unwrap().find("a"),
+        None
+    );
+}
@@ -0,0 +1,91 @@
+use dumb_regex::regex::{Horspool, Match, Prefilter, PrefilterKind, RegularExpression, Searcher};
+
+fn prefilter(pattern: &str) -> Option<Prefilter> {
+    RegularExpression::new(String::from(pattern))
+        .to_ast()
+        .unwrap()
+        .prefilter()
+}
+
+fn find_all(pattern: &str, haystack: &str) -> Vec<(usize, usize)> {
+    let re: RegularExpression = RegularExpression::new(String::from(pattern));
+    let dfa = re
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa()
+        .with_prefilter(re.to_ast().unwrap().prefilter());
+    dfa.find_iter(haystack)
+        .map(|found: Match| (found.start(), found.end()))
+        .collect()
+}
+
+#[test]
+fn literal_analysis_test_1() {
+    let prefilter = prefilter("ERROR \\d+").unwrap();
+
+    assert_eq!(prefilter.literal(), "ERROR ");
+    assert_eq!(prefilter.kind(), PrefilterKind::Prefix);
+}
+
+#[test]
+fn literal_analysis_test_2() {
+    let prefilter = prefilter("\\d+ms(ec)?").unwrap();
+
+    assert_eq!(prefilter.literal(), "ms");
+    assert_eq!(prefilter.kind(), PrefilterKind::Inner);
+}
+
+#[test]
+fn literal_analysis_test_3() {
+    assert_eq!(prefilter("(foo|far)bar").unwrap().literal(), "f");
+    assert_eq!(prefilter("(ab){3}c").unwrap().literal(), "abababc");
+    assert_eq!(prefilter("[a-z]*(xyz|wxyz)").unwrap().literal(), "xyz");
+    assert_eq!(prefilter("a*b").unwrap().literal(), "b");
+    assert!(prefilter("a*[bc]").is_none());
+    assert!(prefilter("foo|bar").is_none());
+}
+
+#[test]
+fn horspool_test() {
+    let searcher = Horspool::new("abcab");
+
+    assert_eq!(searcher.find("xxabcabcab"), Some(2));
+    assert_eq!(searcher.find_at("xxabcabcab", 3), Some(5));
+    assert_eq!(searcher.find_at("xxabcabcab", 6), None);
+    assert_eq!(Horspool::new("é").find("caféé"), Some(3));
+    assert_eq!(Horspool::new("").find_at("abc", 2), Some(2));
+}
+
+#[test]
+fn find_test_1() {
+    assert_eq!(
+        find_all("ERROR \\d+", "ok\nERROR 42 ERROR x ERROR 7\n"),
+        vec![(3, 11), (20, 27)]
+    );
+}
+
+#[test]
+fn find_test_2() {
+    assert_eq!(
+        find_all("\\d+ms", "took 15ms, then 200ms and 3s"),
+        vec![(5, 9), (16, 21)]
+    );
+}
+
+#[test]
+fn find_test_3() {
+    assert_eq!(find_all("a*", "baaéa"), vec![(0, 0), (1, 3), (5, 6)]);
+}
+
+#[test]
+fn find_test_4() {
+    assert_eq!(find_all("(é|ü)+x", "aéüx üx"), vec![(1, 6), (7, 10)]);
+}
+
+#[test]
+fn find_test_5() {
+    let haystack = "x".repeat(100000) + "ERROR 1";
+    assert_eq!(find_all("ERROR \\d", &haystack), vec![(100000, 100007)]);
+}
@@ -0,0 +1,194 @@
+use dumb_regex::regex::{Automaton, MinimizedDfa, RegularExpression, ReverseSearcher};
+
+fn minimized_dfa(pattern: &str) -> MinimizedDfa {
+    RegularExpression::new(pattern.to_string())
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa()
+}
+
+// Every string over a, b and c of at most four chars.
+fn inputs() -> Vec<String> {
+    let mut inputs = vec![String::new()];
+    let mut last = vec![String::new()];
+    for _ in 0..4 {
+        last = last
+            .iter()
+            .flat_map(|input| ['a', 'b', 'c'].map(|c| format!("{input}{c}")))
+            .collect();
+        inputs.extend(last.iter().cloned());
+    }
+    inputs
+}
+
+// Whether an input is in the result.
+type Expected = fn(&str) -> bool;
+
+#[test]
+fn product_test_1() {
+    let ends_with_a = minimized_dfa("(a|b|c)*a");
+    let has_b = minimized_dfa("(a|b|c)*b(a|b|c)*");
+
+    let cases: [(MinimizedDfa, Expected); 5] = [
+        (ends_with_a.intersect(&has_b), |input| {
+            input.ends_with('a') && input.contains('b')
+        }),
+        (ends_with_a.union(&has_b), |input| {
+            input.ends_with('a') || input.contains('b')
+        }),
+        (ends_with_a.difference(&has_b), |input| {
+            input.ends_with('a') && !input.contains('b')
+        }),
+        (has_b.difference(&ends_with_a), |input| {
+            input.contains('b') && !input.ends_with('a')
+        }),
+        (ends_with_a.symmetric_difference(&has_b), |input| {
+            input.ends_with('a') != input.contains('b')
+        }),
+    ];
+    for (minimized_dfa, expected) in cases {
+        for input in inputs() {
+            assert_eq!(
+                minimized_dfa.is_match(&input),
+                expected(&input),
+                "{input:?}"
+            );
+            assert_eq!(minimized_dfa.accepts(input.chars()), expected(&input));
+        }
+    }
+}
+
+#[test]
+fn product_test_2() {
+    // Results are minimal, so they are as small as the DFA of a pattern for
+    // the same strings.
+    let intersection = minimized_dfa("(a|b)*a").intersect(&minimized_dfa("a(a|b)*"));
+    assert_eq!(
+        intersection.state_count(),
+        minimized_dfa("a|a(a|b)*a").state_count()
+    );
+
+    let difference = minimized_dfa("a+").difference(&minimized_dfa("aa"));
+    assert_eq!(
+        difference.state_count(),
+        minimized_dfa("a|aaa+").state_count()
+    );
+    assert!(difference.is_match("a"));
+    assert!(!difference.is_match("aa"));
+    assert!(difference.is_match("aaa"));
+
+    assert!(
+        minimized_dfa("a+")
+            .intersect(&minimized_dfa("b+"))
+            .is_empty()
+    );
+}
+
+#[test]
+fn product_test_3() {
+    let ends_with_abb = minimized_dfa("(a|b)*abb");
+
+    let complement = ends_with_abb.complement(&['a'..='b']);
+    for input in inputs() {
+        let expected = !input.contains('c') && !input.ends_with("abb");
+        assert_eq!(complement.is_match(&input), expected, "{input:?}");
+    }
+    // Complementing twice over the same symbols gives the strings back.
+    let twice = complement.complement(&['a'..='b']);
+    assert_eq!(twice.state_count(), ends_with_abb.state_count());
+    assert!(twice.symmetric_difference(&ends_with_abb).is_empty());
+}
+
+#[test]
+fn product_test_4() {
+    let dfa = |pattern: &str| {
+        RegularExpression::new(pattern.to_string())
+            .to_epsilon_nfa()
+            .unwrap()
+            .to_nfa()
+            .to_dfa()
+    };
+    let words = dfa("[a-c]+");
+    let keywords = dfa("ab|ba");
+
+    let identifiers = words.difference(&keywords);
+    assert!(identifiers.is_match("abc"));
+    assert!(!identifiers.is_match("ab"));
+    assert!(words.intersect(&keywords).is_match("ba"));
+    assert!(words.union(&dfa("d")).is_match("d"));
+
+    let not_keyword = keywords.to_minimized_dfa().complement(&['\0'..='\u{FF}']);
+    assert!(not_keyword.is_match(""));
+    assert!(not_keyword.is_match("é"));
+    assert!(not_keyword.is_match("abé"));
+    assert!(!not_keyword.is_match("λ"));
+    assert!(!not_keyword.is_match("ab"));
+    // Start, after a, after b, the keywords, everything else and the dead
+    // state.
+    assert_eq!(not_keyword.state_count(), 6);
+}
+
+#[test]
+fn product_test_5() {
+    let keywords = minimized_dfa("ab|ba");
+
+    // All of Unicode is taken through default edges, so this stays as small
+    // as the complement over Latin-1.
+    let not_keyword = keywords.complement(&['\0'..=char::MAX]);
+    assert_eq!(not_keyword.state_count(), 6);
+    assert!(not_keyword.is_match(""));
+    assert!(not_keyword.is_match("λ"));
+    assert!(not_keyword.is_match("abλ"));
+    assert!(not_keyword.is_match("\u{10FFFF}"));
+    assert!(!not_keyword.is_match("ab"));
+    assert!(!not_keyword.is_match("ba"));
+
+    let twice = not_keyword.complement(&['\0'..=char::MAX]);
+    assert_eq!(twice.state_count(), keywords.state_count());
+    assert!(twice.symmetric_difference(&keywords).is_empty());
+    assert!(not_keyword.intersect(&keywords).is_empty());
+    assert!(
+        not_keyword
+            .union(&keywords)
+            .complement(&['\0'..=char::MAX])
+            .is_empty()
+    );
+    let mut stream = not_keyword.stream();
+    stream.feed_str("bλ");
+    assert!(stream.finish());
+}
+
+#[test]
+fn product_test_6() {
+    let keywords = minimized_dfa("ab|ba");
+    let not_keyword = keywords.complement(&['\0'..=char::MAX]);
+
+    // The default edges survive going through the NFA, so none of these spell
+    // out all of Unicode.
+    let reversed = not_keyword.reverse();
+    assert!(reversed.is_match("λ"));
+    assert!(reversed.is_match("abλ"));
+    assert!(!reversed.is_match("ba"));
+    assert!(
+        reversed
+            .reverse()
+            .symmetric_difference(&not_keyword)
+            .is_empty()
+    );
+
+    let keyword_then_any = keywords.concat(&not_keyword);
+    assert!(keyword_then_any.is_match("ab"));
+    assert!(keyword_then_any.is_match("baλ"));
+    assert!(!keyword_then_any.is_match("λab"));
+
+    let repeated = not_keyword.star();
+    assert!(repeated.is_match("ab"));
+    assert!(repeated.is_match("\u{10FFFF}ba"));
+
+    assert_eq!(
+        not_keyword.rfind("xyλ").map(|found| found.range()),
+        Some(0..4)
+    );
+}
@@ -0,0 +1,212 @@
+use std::io::{self, Cursor, ErrorKind, Read};
+
+use dumb_regex::regex::{DfaLimits, Engine, MatchKind, RegexBuilder, RegularExpression, Searcher};
+
+// Hands out the input a few bytes at a time, so chars get split across reads.
+struct TrickleReader<'a> {
+    input: &'a [u8],
+    step: usize,
+}
+
+impl Read for TrickleReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.step.min(buf.len()).min(self.input.len());
+        buf[..len].copy_from_slice(&self.input[..len]);
+        self.input = &self.input[len..];
+        Ok(len)
+    }
+}
+
+fn compile(pattern: &str) -> Engine {
+    RegularExpression::new(String::from(pattern))
+        .compile(&DfaLimits::new())
+        .unwrap()
+}
+
+fn reader_matches<R: Read>(engine: &Engine, reader: R) -> Vec<(u64, u64, String)> {
+    engine
+        .find_iter_in_reader(reader)
+        .map(|found| {
+            let found = found.unwrap();
+            (
+                found.start(),
+                found.end(),
+                String::from_utf8(found.into_bytes()).unwrap(),
+            )
+        })
+        .collect()
+}
+
+fn str_matches(engine: &Engine, haystack: &str) -> Vec<(u64, u64, String)> {
+    engine
+        .find_iter(haystack)
+        .map(|found| {
+            (
+                found.start() as u64,
+                found.end() as u64,
+                found.as_str().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn reader_agrees_with_str_test() {
+    let haystack = "ERROR 12 é ok\nERROR x\nwarn ERROR 345é\n";
+    for pattern in ["ERROR \\d+", "é", "ok|warn", "[a-z]*", "(é|E)RROR.{0,3}"] {
+        let engine = compile(pattern);
+        for step in [1, 2, 5, 1000] {
+            let reader = TrickleReader {
+                input: haystack.as_bytes(),
+                step,
+            };
+            assert_eq!(
+                reader_matches(&engine, reader),
+                str_matches(&engine, haystack),
+                "{pattern} {step}"
+            );
+        }
+    }
+}
+
+#[test]
+fn reader_nfa_engine_test() {
+    let re: RegularExpression = RegularExpression::new(String::from("x\\d{2,4}y"));
+    let engine = Engine::EpsilonNfa(re.to_epsilon_nfa().unwrap());
+    let haystack = "x1y x12y x12345y x999y";
+
+    assert_eq!(
+        reader_matches(&engine, Cursor::new(haystack)),
+        vec![
+            (4, 8, String::from("x12y")),
+            (17, 22, String::from("x999y"))
+        ]
+    );
+}
+
+#[test]
+fn reader_large_input_test() {
+    let engine = compile("ERROR \\d+");
+    let mut haystack = "é.".repeat(300000);
+    haystack.push_str("ERROR 42");
+    haystack.push_str(&"-".repeat(200000));
+    haystack.push_str("ERROR 7");
+
+    let matches = reader_matches(&engine, Cursor::new(haystack.as_bytes()));
+    assert_eq!(
+        matches,
+        vec![
+            (900000, 900008, String::from("ERROR 42")),
+            (1100008, 1100015, String::from("ERROR 7"))
+        ]
+    );
+}
+
+#[test]
+fn reader_long_attempt_test() {
+    // An attempt running to the end of the input is followed once, rather
+    // than once per start position.
+    let engine = compile("a*b");
+    let haystack = "a".repeat(200_000);
+    assert!(reader_matches(&engine, Cursor::new(haystack.as_bytes())).is_empty());
+
+    let haystack = format!("{haystack}b");
+    let matches = reader_matches(&engine, Cursor::new(haystack.as_bytes()));
+    assert_eq!(matches.len(), 1);
+    assert_eq!((matches[0].0, matches[0].1), (0, 200_001));
+}
+
+#[test]
+fn reader_match_kind_test() {
+    let haystack = "ab abc xab";
+    for match_kind in [
+        MatchKind::LeftmostFirst,
+        MatchKind::LeftmostLongest,
+        MatchKind::Earliest,
+    ] {
+        for pattern in ["a|ab", "ab|a", "(a|ab)(c|bcd)?", "b*|ab"] {
+            let re = RegexBuilder::new(pattern)
+                .match_kind(match_kind)
+                .build()
+                .unwrap();
+            let engine = re.engine();
+            assert_eq!(
+                reader_matches(engine, Cursor::new(haystack)),
+                str_matches(engine, haystack),
+                "{pattern} {match_kind:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn reader_invalid_utf8_test() {
+    let engine = compile("[ab]+");
+    let mut matches = engine.find_iter_in_reader(Cursor::new(b"aa\xffa".to_vec()));
+
+    assert_eq!(matches.next().unwrap().unwrap().bytes(), b"aa");
+    assert_eq!(
+        matches.next().unwrap().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn reader_invalid_utf8_resume_test() {
+    // "[ab]+c" has no literal prefix and "abb+c" has one, and both report the
+    // invalid byte once and then carry on after it.
+    for pattern in ["[ab]+c", "abb+c"] {
+        let engine = compile(pattern);
+        let results: Vec<io::Result<(u64, u64)>> = engine
+            .find_iter_in_reader(Cursor::new(b"x\xffabbc\xe2\x82abbc".to_vec()))
+            .take(5)
+            .map(|found| found.map(|found| (found.start(), found.end())))
+            .collect();
+
+        assert_eq!(results.len(), 4, "{pattern}");
+        assert_eq!(
+            results[0].as_ref().unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(results[1].as_ref().unwrap(), &(2, 6));
+        assert_eq!(
+            results[2].as_ref().unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(results[3].as_ref().unwrap(), &(8, 12));
+    }
+}
+
+#[test]
+fn find_lines_test() {
+    let engine = compile("\\d+ms");
+    let input = "start\r\nslow: 1200ms\nfast\nretry 3ms then 40ms\n";
+    let lines: Vec<_> = engine
+        .find_lines(Cursor::new(input))
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].line_number(), 2);
+    assert_eq!(lines[0].offset(), 7);
+    assert_eq!(lines[0].line(), "slow: 1200ms");
+    assert_eq!(lines[0].matches().to_vec(), vec![6..12]);
+    assert_eq!(lines[1].line_number(), 4);
+    assert_eq!(lines[1].matches().to_vec(), vec![6..9, 15..19]);
+}
+
+#[test]
+fn find_lines_invalid_utf8_test() {
+    let engine = compile("b");
+    let mut lines = engine.find_lines(Cursor::new(b"a\n\xff\nb\n".to_vec()));
+
+    assert_eq!(
+        lines.next().unwrap().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    let line = lines.next().unwrap().unwrap();
+    assert_eq!(line.line_number(), 3);
+    assert_eq!(line.offset(), 4);
+    assert_eq!(line.line(), "b");
+    assert!(lines.next().is_none());
+}
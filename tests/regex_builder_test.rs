@@ -0,0 +1,269 @@
+use std::io::Cursor;
+
+use dumb_regex::regex::{
+    Engine, EngineKind, Error, MatchKind, MatchKindError, Regex, RegexBuilder, RegularExpression,
+    Searcher,
+};
+
+fn find_all(re: &Regex, haystack: &str) -> Vec<(usize, usize)> {
+    re.find_iter(haystack)
+        .map(|found| (found.start(), found.end()))
+        .collect()
+}
+
+#[test]
+fn regex_builder_test_1() {
+    let re = RegexBuilder::new("hello [a-c]+")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+
+    assert!(re.is_match("HeLLo aBc"));
+    assert!(!re.is_match("HeLLo aBd"));
+    assert!(!Regex::new("hello").unwrap().is_match("Hello"));
+}
+
+#[test]
+fn regex_builder_test_2() {
+    let re = RegexBuilder::new("a.b")
+        .dot_matches_new_line(false)
+        .build()
+        .unwrap();
+
+    assert!(re.is_match("a-b"));
+    assert!(!re.is_match("a\nb"));
+    assert!(Regex::new("a.b").unwrap().is_match("a\nb"));
+}
+
+#[test]
+fn regex_builder_test_3() {
+    let haystack = "12\nab\n345";
+    let single_line = Regex::new("^\\d+$").unwrap();
+    let multi_line = RegexBuilder::new("^\\d+$")
+        .multi_line(true)
+        .build()
+        .unwrap();
+
+    assert_eq!(find_all(&single_line, haystack), vec![]);
+    assert_eq!(find_all(&single_line, "345"), vec![(0, 3)]);
+    assert_eq!(find_all(&multi_line, haystack), vec![(0, 2), (6, 9)]);
+    assert_eq!(find_all(&Regex::new("^a").unwrap(), "aa\na"), vec![(0, 1)]);
+    assert_eq!(find_all(&Regex::new("a$").unwrap(), "aa\na"), vec![(3, 4)]);
+}
+
+#[test]
+fn regex_builder_test_4() {
+    let ascii = Regex::new("\\w+").unwrap();
+    let unicode = RegexBuilder::new("\\w+").unicode(true).build().unwrap();
+
+    assert_eq!(find_all(&ascii, "héllo"), vec![(0, 1), (3, 6)]);
+    assert_eq!(find_all(&unicode, "héllo"), vec![(0, 6)]);
+    assert!(!Regex::new("[^a]").unwrap().is_match("λ"));
+    assert!(
+        RegexBuilder::new("[^a].")
+            .unicode(true)
+            .build()
+            .unwrap()
+            .is_match("λλ")
+    );
+}
+
+#[test]
+fn regex_builder_test_5() {
+    let too_big = RegexBuilder::new("(a|bc){1000}").size_limit(100).build();
+    assert!(matches!(too_big, Err(Error::Compile(_))));
+
+    let too_deep = RegexBuilder::new("(((a)))").nest_limit(2).build();
+    assert!(matches!(too_deep, Err(Error::Syntax(_))));
+    assert!(RegexBuilder::new("(((a)))").nest_limit(3).build().is_ok());
+
+    let dfa_too_big = RegexBuilder::new("(a|b)*a(a|b){12}")
+        .dfa_size_limit(100)
+        .engine(EngineKind::Dfa)
+        .build();
+    assert!(matches!(dfa_too_big, Err(Error::Dfa(err)) if err.is_size_limit()));
+}
+
+#[test]
+fn regex_builder_test_6() {
+    let haystack = "xabb aabb abab ababb";
+    for engine in [
+        EngineKind::Glushkov,
+        EngineKind::EpsilonNfa,
+        EngineKind::Nfa,
+        EngineKind::LazyDfa,
+        EngineKind::Dfa,
+        EngineKind::MinimizedDfa,
+    ] {
+        let re = RegexBuilder::new("(a|b)*abb")
+            .engine(engine)
+            .build()
+            .unwrap();
+        let is_expected_engine = match engine {
+            EngineKind::Glushkov => matches!(re.engine(), Engine::Glushkov(_)),
+            EngineKind::EpsilonNfa => matches!(re.engine(), Engine::EpsilonNfa(_)),
+            EngineKind::Nfa => matches!(re.engine(), Engine::Nfa(_)),
+            EngineKind::LazyDfa => matches!(re.engine(), Engine::LazyDfa(_)),
+            EngineKind::Dfa => matches!(re.engine(), Engine::Dfa(_)),
+            EngineKind::MinimizedDfa => matches!(re.engine(), Engine::MinimizedDfa(_)),
+        };
+
+        assert!(is_expected_engine, "{engine:?}");
+        assert!(re.is_match("babb"));
+        assert_eq!(find_all(&re, haystack), vec![(1, 4), (5, 9), (15, 20)]);
+    }
+}
+
+#[test]
+fn regex_builder_test_7() {
+    let re = RegexBuilder::new("(a|ab)(c|b)?")
+        .match_kind(MatchKind::LeftmostFirst)
+        .build()
+        .unwrap();
+    assert_eq!(re.find("abc").unwrap().as_str(), "ab");
+
+    let forced = RegexBuilder::new("(a|ab)(c|b)?")
+        .match_kind(MatchKind::LeftmostFirst)
+        .engine(EngineKind::Dfa)
+        .build();
+    assert_eq!(
+        forced.unwrap_err(),
+        Error::MatchKind(MatchKindError {
+            engine: EngineKind::Dfa,
+            match_kind: MatchKind::LeftmostFirst,
+        })
+    );
+
+    let anchored = RegexBuilder::new("^(a|ab)$")
+        .match_kind(MatchKind::LeftmostFirst)
+        .multi_line(true)
+        .build()
+        .unwrap();
+    assert_eq!(find_all(&anchored, "ab\na\nb"), vec![(0, 2), (3, 4)]);
+}
+
+#[test]
+fn regex_builder_test_8() {
+    // Whole strings always start and end where the anchors hold, so the
+    // automata leave them out. Only the facade searches with them.
+    assert_eq!(
+        RegularExpression::new("^a|^b$".to_string()).to_ast(),
+        RegularExpression::new("a|b$".to_string()).to_ast()
+    );
+    assert_eq!(
+        RegularExpression::new("^a$".to_string()).to_ast(),
+        RegularExpression::new("a".to_string()).to_ast()
+    );
+    assert!(Regex::new("a^").is_err());
+
+    let re = Regex::new("^[a-z]+$").unwrap();
+    let lines: Vec<u64> = re
+        .find_lines(Cursor::new("abc\nab1\nxyz\n"))
+        .map(|line| line.unwrap().line_number())
+        .collect();
+    assert_eq!(lines, vec![1, 3]);
+}
+
+#[test]
+fn regex_builder_test_9() {
+    // Classes too large for a DFA go to the Glushkov engine while the pattern
+    // is short enough for it.
+    let short = RegexBuilder::new("a.*b").unicode(true).build().unwrap();
+    assert!(matches!(short.engine(), Engine::Glushkov(_)));
+    assert_eq!(find_all(&short, "xa日本bb"), vec![(1, 10)]);
+    assert!(!short.is_match("a日本"));
+
+    let long = RegexBuilder::new("[^x]{70}").unicode(true).build().unwrap();
+    assert!(matches!(long.engine(), Engine::EpsilonNfa(_)));
+    assert!(long.is_match(&"λ".repeat(70)));
+}
+
+#[test]
+fn regex_builder_test_10() {
+    // Every alternative can carry the anchors, as long as they all do.
+    let re = Regex::new("^a|^bc").unwrap();
+    assert_eq!(find_all(&re, "bca"), vec![(0, 2)]);
+    assert_eq!(find_all(&re, "abc"), vec![(0, 1)]);
+    assert_eq!(find_all(&re, "cbc"), vec![]);
+
+    let lines = RegexBuilder::new("^a+$|^b+$")
+        .multi_line(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        find_all(
+            &lines,
+            "aa
+ab
+bbb"
+        ),
+        vec![(0, 2), (6, 9)]
+    );
+
+    assert!(matches!(Regex::new("^a|b"), Err(Error::Syntax(_))));
+    assert!(matches!(Regex::new("a$|b"), Err(Error::Syntax(_))));
+
+    // The anchors are checked during the single pass over the haystack, with
+    // every engine.
+    let haystack = format!("{}b", "a".repeat(50_000));
+    for engine in [
+        EngineKind::Nfa,
+        EngineKind::LazyDfa,
+        EngineKind::Dfa,
+        EngineKind::MinimizedDfa,
+    ] {
+        let re = RegexBuilder::new("a+$").engine(engine).build().unwrap();
+        assert_eq!(find_all(&re, &haystack), vec![], "{engine:?}");
+        let re = RegexBuilder::new("^a+").engine(engine).build().unwrap();
+        assert_eq!(find_all(&re, &haystack), vec![(0, 50_000)], "{engine:?}");
+    }
+}
+
+#[test]
+fn regex_builder_test_11() {
+    // `\d` is the decimal digits of every script, not every numeric char.
+    let digits = RegexBuilder::new("\\d+").unicode(true).build().unwrap();
+    assert!(digits.is_match("٣٤"));
+    assert!(digits.is_match("𝟘𝟡"));
+    assert!(digits.is_match("७"));
+    assert!(!digits.is_match("²"));
+    assert!(!digits.is_match("½"));
+    assert!(!digits.is_match("Ⅻ"));
+
+    let not_digits = RegexBuilder::new("\\D").unicode(true).build().unwrap();
+    assert!(not_digits.is_match("½"));
+    assert!(!not_digits.is_match("٣"));
+}
+
+#[test]
+fn regex_builder_test_12() {
+    // Searching a reader applies the anchors the way find_iter does.
+    let reader_all = |re: &Regex, haystack: &str| -> Vec<(usize, usize)> {
+        re.find_iter_in_reader(Cursor::new(haystack))
+            .map(|found| {
+                let found = found.unwrap();
+                (found.start() as usize, found.end() as usize)
+            })
+            .collect()
+    };
+    let cases = [
+        ("^ab", false, "xab ab"),
+        ("^ab", false, "ab ab"),
+        ("ab$", false, "ab ab\nab"),
+        ("^ab", true, "ab\nxab\nab"),
+        ("ab$", true, "ab\nab x\nab"),
+        ("^a*$", true, "aa\n\nab\na"),
+    ];
+    for (pattern, multi_line, haystack) in cases {
+        let re = RegexBuilder::new(pattern)
+            .multi_line(multi_line)
+            .build()
+            .unwrap();
+        assert_eq!(
+            reader_all(&re, haystack),
+            find_all(&re, haystack),
+            "{pattern} {haystack:?}"
+        );
+    }
+    assert!(reader_all(&Regex::new("^ab").unwrap(), "xab ab").is_empty());
+}
@@ -0,0 +1,59 @@
+use std::{sync::Arc, thread};
+
+use dumb_regex::regex::{Engine, Error, Regex, Searcher};
+
+#[test]
+fn regex_test_1() {
+    let re = Regex::new("[+-]?(\\d+(\\.\\d*)?|\\.\\d+)([eE][+-]?\\d+)?").unwrap();
+
+    assert!(re.is_match("3.14159"));
+    assert!(re.is_match("-.5e10"));
+    assert!(!re.is_match("3.14.159"));
+    let found: Vec<&str> = re.find_iter("x=1.5, y=-2e3").map(|m| m.as_str()).collect();
+    assert_eq!(found, vec!["1.5", "-2e3"]);
+}
+
+#[test]
+fn regex_test_2() {
+    assert!(matches!(Regex::new("(a"), Err(Error::Syntax(_))));
+    let err = "a)".parse::<Regex>().unwrap_err();
+    assert!(err.to_string().starts_with("syntax error"));
+}
+
+#[test]
+fn regex_test_3() {
+    let re: Regex = "(a|b)*abb".parse().unwrap();
+    let copy = re.clone();
+
+    assert_eq!(re.to_string(), "(a|b)*abb");
+    assert_eq!(copy.as_str(), "(a|b)*abb");
+    assert_eq!(format!("{re:?}"), "Regex(\"(a|b)*abb\")");
+    assert!(copy.is_match("aababb"));
+}
+
+#[test]
+fn regex_test_4() {
+    // Too many DFA states to build eagerly, so they are built as needed.
+    let re = Regex::new("(a|b)*a(a|b){40}").unwrap();
+
+    assert!(matches!(re.engine(), Engine::LazyDfa(_)));
+    assert!(re.is_match(&format!("bbb{}", "a".repeat(41))));
+}
+
+#[test]
+fn regex_test_5() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Regex>();
+
+    let re = Arc::new(Regex::new("[a-z]+@[a-z]+\\.[a-z]{2,}").unwrap());
+    let workers: Vec<_> = ["john@example.com", "jane@example", "a@b.cd"]
+        .into_iter()
+        .map(|input| {
+            let re = Arc::clone(&re);
+            thread::spawn(move || re.is_match(input))
+        })
+        .collect();
+    let results: Vec<bool> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+
+    assert_eq!(results, vec![true, false, true]);
+}
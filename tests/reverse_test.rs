@@ -0,0 +1,231 @@
+use dumb_regex::regex::{
+    AhoCorasick, EngineKind, Literal, MatchKind, Regex, RegexBuilder, RegularExpression,
+    ReverseSearcher, Searcher,
+};
+
+const PATTERNS: [&str; 6] = [
+    "abc",
+    "(a|b)*abb",
+    "x\\d{2,4}y",
+    "(ab|é){2,3}c?",
+    "[a-z]+@[a-z]+\\.[a-z]{2,}",
+    "a*",
+];
+
+const INPUTS: [&str; 10] = [
+    "",
+    "abc",
+    "ababb",
+    "x123y",
+    "x12345y",
+    "abéab",
+    "éabéc",
+    "john@example.com",
+    "aaaa",
+    "cba",
+];
+
+fn reversed(input: &str) -> String {
+    input.chars().rev().collect()
+}
+
+#[test]
+fn reverse_ast_test() {
+    for pattern in PATTERNS {
+        let re: RegularExpression = RegularExpression::new(String::from(pattern));
+        let forward = re.to_epsilon_nfa().unwrap();
+        let backward = re.to_ast().unwrap().reverse().to_epsilon_nfa().unwrap();
+        for input in INPUTS {
+            assert_eq!(
+                backward.is_match(&reversed(input)),
+                forward.is_match(input),
+                "{pattern} {input}"
+            );
+        }
+    }
+}
+
+#[test]
+fn reverse_epsilon_nfa_test() {
+    for pattern in PATTERNS {
+        let re: RegularExpression = RegularExpression::new(String::from(pattern));
+        let forward = re.to_epsilon_nfa().unwrap();
+        let backward = forward.reverse();
+        for input in INPUTS {
+            assert_eq!(
+                backward.is_match(&reversed(input)),
+                forward.is_match(input),
+                "{pattern} {input}"
+            );
+            assert_eq!(
+                backward.to_nfa().is_match(&reversed(input)),
+                forward.is_match(input),
+                "{pattern} {input}"
+            );
+        }
+    }
+}
+
+#[test]
+fn reverse_nfa_test() {
+    for pattern in PATTERNS {
+        let re: RegularExpression = RegularExpression::new(String::from(pattern));
+        let forward = re.to_epsilon_nfa().unwrap().to_nfa();
+        let backward = forward.reverse();
+        for input in INPUTS {
+            assert_eq!(
+                backward.is_match(&reversed(input)),
+                forward.is_match(input),
+                "{pattern} {input}"
+            );
+        }
+    }
+}
+
+#[test]
+fn reverse_dfa_test() {
+    for pattern in PATTERNS {
+        let re: RegularExpression = RegularExpression::new(String::from(pattern));
+        let forward = re.to_epsilon_nfa().unwrap().to_nfa().to_dfa();
+        let backward = forward.reverse();
+        for input in INPUTS {
+            assert_eq!(
+                backward.is_match(&reversed(input)),
+                forward.is_match(input),
+                "{pattern} {input}"
+            );
+            assert_eq!(
+                backward.reverse().is_match(input),
+                forward.is_match(input),
+                "{pattern} {input}"
+            );
+        }
+    }
+}
+
+#[test]
+fn rfind_test_1() {
+    let re: RegularExpression = RegularExpression::new(String::from("\\d+"));
+    let minimized_dfa = re
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa();
+    let haystack = "a1b22c333d";
+
+    assert_eq!(minimized_dfa.rfind(haystack).unwrap().as_str(), "333");
+    assert_eq!(minimized_dfa.rfind_at(haystack, 5).unwrap().range(), 3..5);
+    assert_eq!(minimized_dfa.rfind_at(haystack, 4).unwrap().range(), 3..4);
+    let matches: Vec<&str> = minimized_dfa
+        .rfind_iter(haystack)
+        .map(|found| found.as_str())
+        .collect();
+    assert_eq!(matches, vec!["333", "22", "1"]);
+}
+
+#[test]
+fn rfind_test_2() {
+    let re: RegularExpression = RegularExpression::new(String::from("a*"));
+    let minimized_dfa = re
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa();
+    let matches: Vec<(usize, usize)> = minimized_dfa
+        .rfind_iter("baaéa")
+        .map(|found| (found.start(), found.end()))
+        .collect();
+
+    assert_eq!(matches, vec![(5, 6), (1, 3), (0, 0)]);
+}
+
+#[test]
+fn rfind_literal_test() {
+    let literal = Literal::new("ab");
+    let matches: Vec<usize> = literal
+        .rfind_iter("abababxab")
+        .map(|found| found.start())
+        .collect();
+
+    assert_eq!(matches, vec![7, 4, 2, 0]);
+}
+
+#[test]
+fn reverse_literal_set_test() {
+    // The reversed patterns keep their order, which leftmost-first goes by.
+    for (patterns, expected) in [(["bc", "abc"], 0..2), (["abc", "bc"], 0..3)] {
+        let reversed = AhoCorasick::new(patterns)
+            .with_match_kind(MatchKind::LeftmostFirst)
+            .reverse();
+        assert_eq!(reversed.match_kind(), MatchKind::LeftmostFirst);
+        assert_eq!(reversed.find("cba").unwrap().range(), expected);
+    }
+}
+
+#[test]
+fn reverse_glushkov_test() {
+    let glushkov = RegularExpression::new("(a|b)*abb".to_string())
+        .to_glushkov()
+        .unwrap()
+        .with_match_kind(MatchKind::Earliest)
+        .unwrap();
+
+    assert_eq!(glushkov.reverse().match_kind(), MatchKind::Earliest);
+}
+
+#[test]
+fn rfind_test_3() {
+    let cases = [
+        ("\\d+", false, "a1b22c333d", vec![(6, 9), (3, 5), (1, 2)]),
+        ("^a+|^b", false, "aab aab", vec![(0, 2)]),
+        ("\\d+$", false, "12 34", vec![(3, 5)]),
+        (
+            "foo|bar|baz",
+            false,
+            "foobarxbaz",
+            vec![(7, 10), (3, 6), (0, 3)],
+        ),
+        ("(a|b)*abb", false, "ababbxabb", vec![(6, 9), (0, 5)]),
+        ("^\\w+$", true, "ab\ncd", vec![(3, 5), (0, 2)]),
+        ("é+", false, "aééaé", vec![(6, 8), (1, 5)]),
+    ];
+    let engines = [
+        None,
+        Some(EngineKind::EpsilonNfa),
+        Some(EngineKind::Nfa),
+        Some(EngineKind::LazyDfa),
+        Some(EngineKind::Dfa),
+        Some(EngineKind::MinimizedDfa),
+    ];
+    for (pattern, multi_line, haystack, expected) in cases {
+        for engine in engines {
+            let mut builder = RegexBuilder::new(pattern).multi_line(multi_line);
+            if let Some(engine) = engine {
+                builder = builder.engine(engine);
+            }
+            let regex = builder.build().unwrap();
+            let matches: Vec<(usize, usize)> = regex
+                .rfind_iter(haystack)
+                .map(|found| (found.start(), found.end()))
+                .collect();
+            assert_eq!(matches, expected, "{pattern} {engine:?}");
+            assert_eq!(
+                regex.engine().rfind(haystack).is_some(),
+                !expected.is_empty(),
+                "{pattern} {engine:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn rfind_test_4() {
+    let regex = Regex::new("\\d+$").unwrap();
+    let haystack = "1 22 333";
+
+    assert_eq!(regex.rfind(haystack).unwrap().range(), 5..8);
+    assert_eq!(regex.rfind_at(haystack, 4), None);
+    assert_eq!(regex.engine().rfind_at(haystack, 4).unwrap().range(), 2..4);
+}
@@ -0,0 +1,154 @@
+use dumb_regex::regex::{DfaLimits, Engine, RegularExpression, StreamMatcher};
+
+const PATTERNS: [&str; 6] = [
+    "hello",
+    "foo|bar|é",
+    "(a|é)*b",
+    "[a-z]+@[a-z]+\\.[a-z]{2,60}",
+    "x(é|ab){2,5}y",
+    "(a|b)*a(a|b){40}",
+];
+
+const INPUTS: [&str; 9] = [
+    "",
+    "hello",
+    "é",
+    "aééab",
+    "john@example.com",
+    "xéabéy",
+    "xéy",
+    "bar",
+    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+];
+
+fn feed_in_chunks(mut matcher: StreamMatcher, input: &str, chunk_len: usize) -> bool {
+    for chunk in input.as_bytes().chunks(chunk_len) {
+        matcher.feed(chunk);
+    }
+    matcher.finish()
+}
+
+fn engines(pattern: &str) -> Vec<Engine> {
+    let re: RegularExpression = RegularExpression::new(String::from(pattern));
+    let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+    let nfa = epsilon_nfa.to_nfa();
+
+    let mut engines = vec![re.compile(&DfaLimits::new().max_states(1000)).unwrap()];
+    if let Ok(dfa) = nfa.to_dfa_with_limits(&DfaLimits::new().max_states(1000)) {
+        engines.push(Engine::MinimizedDfa(dfa.to_minimized_dfa()));
+    }
+    engines.push(Engine::Nfa(nfa));
+    engines.push(Engine::EpsilonNfa(epsilon_nfa));
+    engines
+}
+
+#[test]
+fn stream_agrees_with_is_match_test() {
+    for pattern in PATTERNS {
+        for engine in engines(pattern) {
+            for input in INPUTS {
+                for chunk_len in [1, 2, 3, 7, 64] {
+                    assert_eq!(
+                        feed_in_chunks(engine.stream(), input, chunk_len),
+                        engine.is_match(input),
+                        "{pattern} {input} {chunk_len}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn stream_split_utf8_test() {
+    let re: RegularExpression = RegularExpression::new(String::from("(€|a)*"));
+    let minimized_dfa = re
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa();
+    let mut matcher = minimized_dfa.stream();
+
+    matcher.feed(&"a€".as_bytes()[..2]);
+    assert!(!matcher.is_accepting());
+    assert!(!matcher.is_dead());
+
+    matcher.feed(&"€".as_bytes()[1..2]);
+    assert!(!matcher.is_accepting());
+
+    matcher.feed(&"€".as_bytes()[2..]);
+    assert!(matcher.is_accepting());
+    assert!(matcher.finish());
+}
+
+#[test]
+fn stream_invalid_utf8_test() {
+    let re: RegularExpression = RegularExpression::new(String::from("a*"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+    let mut matcher = nfa.stream();
+
+    matcher.feed(b"aa\xffaa");
+    matcher.feed(b"aa\xff");
+    assert!(matcher.is_dead());
+    assert!(!matcher.finish());
+
+    let mut matcher = nfa.stream();
+    matcher.feed(b"aa\xe2\x82");
+    assert!(!matcher.finish());
+}
+
+#[test]
+fn stream_invalid_lead_byte_test() {
+    // Bytes 0xF8 to 0xFF never start a char, so they end the stream at once
+    // instead of waiting for continuation bytes.
+    let re: RegularExpression = RegularExpression::new(String::from("a*"));
+    let nfa = re.to_epsilon_nfa().unwrap().to_nfa();
+    for lead in 0xF8..=0xFF {
+        let mut matcher = nfa.stream();
+        matcher.feed(b"a");
+        matcher.feed(&[lead]);
+        assert!(matcher.is_dead(), "{lead:#x}");
+        matcher.feed(b"\x80\x80\x80");
+        assert!(!matcher.finish(), "{lead:#x}");
+    }
+
+    let mut matcher = nfa.stream();
+    matcher.feed(b"a\xf0\x9f");
+    assert!(!matcher.is_dead());
+    matcher.feed(b"\x98\x80");
+    assert!(!matcher.finish());
+}
+
+#[test]
+fn stream_dead_test() {
+    let re: RegularExpression = RegularExpression::new(String::from("ab*c"));
+    let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+    let mut matcher = epsilon_nfa.stream();
+
+    matcher.feed_str("abbb");
+    assert!(!matcher.is_dead());
+    matcher.feed_str("cc");
+    assert!(matcher.is_dead());
+    assert!(!matcher.finish());
+}
+
+#[test]
+fn stream_snapshot_test() {
+    let re: RegularExpression = RegularExpression::new(String::from("x\\d{2,3}y"));
+    let epsilon_nfa = re.to_epsilon_nfa().unwrap();
+    let mut matcher = epsilon_nfa.stream();
+    matcher.feed_str("x1");
+
+    let snapshot = matcher.clone();
+    matcher.feed_str("2y");
+    assert!(matcher.finish());
+
+    let mut resumed = snapshot.clone();
+    resumed.feed_str("234y");
+    assert!(!resumed.finish());
+
+    let mut resumed = snapshot;
+    resumed.feed_str("23y");
+    assert!(resumed.finish());
+}
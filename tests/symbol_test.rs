@@ -0,0 +1,149 @@
+use dumb_regex::regex::{Ast, Automaton, DfaBuilder, NfaBuilder, Symbol, SymbolBuildError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Token {
+    Ident,
+    Number,
+    Comma,
+    LParen,
+    RParen,
+}
+
+impl Symbol for Token {
+    fn successor(self) -> Option<Token> {
+        match self {
+            Ident => Some(Number),
+            Number => Some(Comma),
+            Comma => Some(LParen),
+            LParen => Some(RParen),
+            RParen => None,
+        }
+    }
+}
+
+use Token::*;
+
+// A call with a comma separated list of arguments: f(a, 1, b).
+fn call() -> Ast<Token> {
+    let argument = Ast::class([Ident, Number]);
+    let arguments = Ast::concat(
+        argument.clone(),
+        Ast::repeat(Ast::concat(Ast::literal(Comma), argument), 0, None),
+    );
+    Ast::Concat(vec![
+        Ast::literal(Ident),
+        Ast::literal(LParen),
+        Ast::repeat(arguments, 0, Some(1)),
+        Ast::literal(RParen),
+    ])
+}
+
+#[test]
+fn symbol_test_1() {
+    let epsilon_nfa = call().to_epsilon_nfa().unwrap();
+    let nfa = epsilon_nfa.to_nfa();
+    let dfa = nfa.to_dfa();
+    let minimized_dfa = dfa.to_minimized_dfa();
+
+    let inputs: Vec<(Vec<Token>, bool)> = vec![
+        (vec![Ident, LParen, RParen], true),
+        (vec![Ident, LParen, Number, RParen], true),
+        (
+            vec![Ident, LParen, Ident, Comma, Number, Comma, Ident, RParen],
+            true,
+        ),
+        (vec![Ident, LParen, Comma, RParen], false),
+        (vec![Ident, LParen, Number, Comma, RParen], false),
+        (vec![Number, LParen, RParen], false),
+        (vec![], false),
+    ];
+    for (input, expected) in inputs {
+        assert_eq!(epsilon_nfa.accepts(input.clone()), expected, "{input:?}");
+        assert_eq!(nfa.accepts(input.clone()), expected, "{input:?}");
+        assert_eq!(dfa.accepts(input.clone()), expected, "{input:?}");
+        assert_eq!(minimized_dfa.accepts(input.clone()), expected, "{input:?}");
+    }
+    // Start, after the name, after `(`, after an argument, after `,` and
+    // after `)`, plus the dead state.
+    assert_eq!(minimized_dfa.state_count(), 7);
+    let symbols: Vec<Token> = minimized_dfa.transitions().map(|(_, c, _)| c).collect();
+    assert!(symbols.contains(&Comma));
+}
+
+#[test]
+fn symbol_test_2() {
+    // Counted repetitions and reversal work on any symbol.
+    let pattern = Ast::concat(
+        Ast::repeat(Ast::literal(Number), 2, Some(3)),
+        Ast::literal(Comma),
+    );
+    let nfa = pattern.reverse().to_epsilon_nfa().unwrap().to_nfa();
+    let minimized_dfa = nfa.to_dfa().to_minimized_dfa();
+    assert!(minimized_dfa.accepts([Comma, Number, Number]));
+    assert!(minimized_dfa.accepts([Comma, Number, Number, Number]));
+    assert!(!minimized_dfa.accepts([Comma, Number]));
+    assert!(!minimized_dfa.accepts([Number, Number, Comma]));
+    assert!(minimized_dfa.reverse().accepts([Number, Number, Comma]));
+}
+
+#[test]
+fn symbol_test_3() {
+    // Bytes have a successor, so classes can be ranges.
+    let hex = Ast::class_ranges([b'0'..=b'9', b'a'..=b'f']);
+    let pattern = Ast::concat(Ast::literal(b'#'), Ast::repeat(hex, 1, None));
+    let dfa = pattern.to_epsilon_nfa().unwrap().to_nfa().to_dfa();
+    assert!(dfa.accepts(b"#1f".iter().copied()));
+    assert!(dfa.accepts(b"#c0ffee".iter().copied()));
+    assert!(!dfa.accepts(b"#g".iter().copied()));
+    assert!(!dfa.accepts(b"1f".iter().copied()));
+    assert_eq!(Ast::class(b'a'..=b'c'), Ast::Class(vec![b'a'..=b'c']));
+}
+
+#[test]
+fn symbol_test_4() {
+    let mut builder = NfaBuilder::default();
+    let start = builder.add_state();
+    let end = builder.add_state();
+    builder.set_start(start).unwrap();
+    builder.add_transition(start, LParen, start).unwrap();
+    builder.add_transition(start, LParen, end).unwrap();
+    builder.add_transition(end, RParen, end).unwrap();
+    builder.set_accepting(end).unwrap();
+    let minimized_dfa = builder.build().unwrap().to_dfa().to_minimized_dfa();
+    assert!(minimized_dfa.accepts([LParen, LParen, RParen]));
+    assert!(!minimized_dfa.accepts([RParen]));
+
+    let mut builder = DfaBuilder::default();
+    let start = builder.add_state();
+    builder.set_start(start).unwrap();
+    builder.add_transition(start, Ident, start).unwrap();
+    assert_eq!(
+        builder.add_transition(start, Ident, 0),
+        Err(SymbolBuildError::UnknownState(0))
+    );
+    let other = builder.add_state();
+    assert_eq!(
+        builder.add_transition(start, Ident, other),
+        Err(SymbolBuildError::Nondeterministic {
+            state: start,
+            symbol: Ident
+        })
+    );
+}
+
+#[test]
+fn symbol_test_5() {
+    // A range of tokens holds every token from its start to its end.
+    let class = Ast::class_ranges([Ident..=Comma]);
+    assert_eq!(class, Ast::class([Ident, Number, Comma]));
+    let minimized_dfa = class
+        .to_epsilon_nfa()
+        .unwrap()
+        .to_nfa()
+        .to_dfa()
+        .to_minimized_dfa();
+    assert!(minimized_dfa.accepts([Ident]));
+    assert!(minimized_dfa.accepts([Number]));
+    assert!(minimized_dfa.accepts([Comma]));
+    assert!(!minimized_dfa.accepts([LParen]));
+}